@@ -0,0 +1,153 @@
+use std::{collections::BTreeMap, error::Error, sync::Arc};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    response::Response,
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    lifecycle::ArbitrageChange,
+    live_state::{LiveState, ProviderHealth},
+    models::SportEvent,
+};
+
+#[derive(Serialize)]
+struct EventsResponse {
+    matched_at: Option<DateTime<Utc>>,
+    events: BTreeMap<String, Vec<SportEvent>>,
+}
+
+#[derive(Deserialize)]
+struct ArbitragesQuery {
+    /// Only arbitrages guaranteeing at least this return, in percent.
+    #[serde(default)]
+    min_profit: f64,
+    /// Every matched event instead of only the arbitrages.
+    #[serde(default)]
+    all: bool,
+}
+
+#[derive(Serialize)]
+struct ArbitragesResponse {
+    matched_at: Option<DateTime<Utc>>,
+    arbitrages: Vec<PossibleArbitrage>,
+}
+
+#[derive(Serialize)]
+struct ProviderHealthResponse {
+    provider: String,
+    status: &'static str,
+    #[serde(flatten)]
+    health: ProviderHealth,
+}
+
+/// Serves the watch's latest state as JSON on `address` in the background:
+///
+/// - `GET /events` - every provider's latest odds
+/// - `GET /arbitrages?min_profit=&all=` - arbitrages of the latest matching, every matched event
+///   with `all=true`
+/// - `GET /providers/health` - when each provider last succeeded and failed
+/// - `GET /stream` - WebSocket pushing a JSON message whenever an arbitrage appears, changes or
+///   disappears
+pub async fn start(address: &str, state: Arc<LiveState>) -> Result<(), Box<dyn Error>> {
+    let listener = tokio::net::TcpListener::bind(address)
+        .await
+        .map_err(|e| format!("couldn't listen on {}: {}", address, e))?;
+
+    let app = Router::new()
+        .route("/events", get(events))
+        .route("/arbitrages", get(arbitrages))
+        .route("/providers/health", get(providers_health))
+        .route("/stream", get(stream))
+        .with_state(state);
+
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            eprintln!("API server stopped: {}", e);
+        }
+    });
+
+    Ok(())
+}
+
+async fn events(State(state): State<Arc<LiveState>>) -> Json<EventsResponse> {
+    let snapshot = state.snapshot();
+    Json(EventsResponse {
+        matched_at: snapshot.matched_at,
+        events: snapshot.events,
+    })
+}
+
+async fn arbitrages(
+    State(state): State<Arc<LiveState>>,
+    Query(query): Query<ArbitragesQuery>,
+) -> Json<ArbitragesResponse> {
+    let snapshot = state.snapshot();
+    let arbitrages = snapshot
+        .arbitrages
+        .into_iter()
+        .filter(|arbitrage| {
+            query.all || (arbitrage.is_arbitrage && arbitrage.guaranteed_return >= query.min_profit)
+        })
+        .collect();
+
+    Json(ArbitragesResponse {
+        matched_at: snapshot.matched_at,
+        arbitrages,
+    })
+}
+
+async fn providers_health(
+    State(state): State<Arc<LiveState>>,
+) -> Json<Vec<ProviderHealthResponse>> {
+    let providers = state
+        .snapshot()
+        .providers
+        .into_iter()
+        .map(|(provider, health)| ProviderHealthResponse {
+            provider,
+            status: health.status(),
+            health,
+        })
+        .collect();
+
+    Json(providers)
+}
+
+async fn stream(State(state): State<Arc<LiveState>>, upgrade: WebSocketUpgrade) -> Response {
+    // Subscribed right away so nothing published during the handshake is missed
+    let changes = state.subscribe();
+    upgrade.on_upgrade(move |socket| forward_changes(socket, changes))
+}
+
+async fn forward_changes(mut socket: WebSocket, mut changes: broadcast::Receiver<ArbitrageChange>) {
+    loop {
+        let change = match changes.recv().await {
+            Ok(change) => change,
+            // A client too slow to keep up misses the oldest changes rather than holding up others
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return,
+        };
+
+        let message = match serde_json::to_string(&change) {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("couldn't serialize arbitrage change: {}", e);
+                continue;
+            }
+        };
+        if socket.send(Message::Text(message.into())).await.is_err() {
+            // The client went away
+            return;
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{fees::Fees, models::SportEvent};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PossibleArbitrage {
+    /// Fixture and the providers of each outcome when first seen, see `arbitrage_id`. Kept by
+    /// `ArbitrageTracker` for as long as the fixture stays an arbitrage, even as prices move.
+    #[serde(default)]
+    pub id: String,
+    pub team1: String,
+    pub team2: String,
+    pub kof1: f64,
+    pub kof_draw: f64,
+    pub kof2: f64,
+    pub kof1_provider: String,
+    pub kof_draw_provider: String,
+    pub kof2_provider: String,
+    /// Odds after the provider's fees and taxes, used for picking the best prices.
+    pub effective_kof1: f64,
+    pub effective_kof_draw: f64,
+    pub effective_kof2: f64,
+    /// Sum of implied probabilities (1 / effective odds) of the best 1/X/2 prices.
+    pub overround: f64,
+    /// Combined book margin in percent. Negative when the prices form an arbitrage.
+    pub margin: f64,
+    /// Return in percent guaranteed by staking every outcome proportionally to its implied probability.
+    pub guaranteed_return: f64,
+    pub is_arbitrage: bool,
+    pub providers_offering_bets: Vec<String>,
+    /// Every provider's odds for this event, with teams in the same order as `team1` and `team2`.
+    pub events: Vec<SportEvent>,
+}
+
+pub fn find_arbitrages(
+    events_by_provider: Vec<Vec<SportEvent>>,
+    fees: &Fees,
+) -> Result<(Vec<PossibleArbitrage>, Vec<SportEvent>), Box<dyn Error>> {
+    let grouped_events = group_events(events_by_provider)?;
+
+    let mut events_wo_pairs = vec![];
+    let mut possible_arbitrages = vec![];
+    for mut event_group in grouped_events {
+        if event_group.len() == 1 {
+            events_wo_pairs.push(event_group.pop().ok_or("")?);
+            continue;
+        }
+
+        let first_event = &event_group[0];
+        let mut possible_arbitrage = PossibleArbitrage {
+            id: String::new(),
+            team1: first_event.team1.clone(),
+            team2: first_event.team2.clone(),
+            kof1: first_event.kof1,
+            kof_draw: first_event.kof_draw,
+            kof2: first_event.kof2,
+            kof1_provider: first_event.provider.clone(),
+            kof_draw_provider: first_event.provider.clone(),
+            kof2_provider: first_event.provider.clone(),
+            effective_kof1: fees.effective_kof(&first_event.provider, first_event.kof1),
+            effective_kof_draw: fees.effective_kof(&first_event.provider, first_event.kof_draw),
+            effective_kof2: fees.effective_kof(&first_event.provider, first_event.kof2),
+            overround: 0.0,
+            margin: 0.0,
+            guaranteed_return: 0.0,
+            is_arbitrage: false,
+            providers_offering_bets: vec![first_event.provider.clone()],
+            events: vec![],
+        };
+
+        for event in event_group.iter().skip(1) {
+            let effective_kof1 = fees.effective_kof(&event.provider, event.kof1);
+            let effective_kof_draw = fees.effective_kof(&event.provider, event.kof_draw);
+            let effective_kof2 = fees.effective_kof(&event.provider, event.kof2);
+
+            if effective_kof1 > possible_arbitrage.effective_kof1 {
+                possible_arbitrage.kof1 = event.kof1;
+                possible_arbitrage.effective_kof1 = effective_kof1;
+                possible_arbitrage.kof1_provider = event.provider.clone();
+            }
+            if effective_kof2 > possible_arbitrage.effective_kof2 {
+                possible_arbitrage.kof2 = event.kof2;
+                possible_arbitrage.effective_kof2 = effective_kof2;
+                possible_arbitrage.kof2_provider = event.provider.clone();
+            }
+            if effective_kof_draw > possible_arbitrage.effective_kof_draw {
+                possible_arbitrage.kof_draw = event.kof_draw;
+                possible_arbitrage.effective_kof_draw = effective_kof_draw;
+                possible_arbitrage.kof_draw_provider = event.provider.clone();
+            }
+
+            possible_arbitrage
+                .providers_offering_bets
+                .push(event.provider.clone())
+        }
+
+        possible_arbitrage.mark_is_arbitrage();
+        possible_arbitrage.id = arbitrage_id(&possible_arbitrage);
+        possible_arbitrage.events = event_group;
+
+        possible_arbitrages.push(possible_arbitrage);
+    }
+
+    Ok((possible_arbitrages, events_wo_pairs))
+}
+
+impl PossibleArbitrage {
+    fn mark_is_arbitrage(&mut self) {
+        self.overround =
+            1.0 / self.effective_kof1 + 1.0 / self.effective_kof_draw + 1.0 / self.effective_kof2;
+        self.margin = (self.overround - 1.0) * 100.0;
+        self.guaranteed_return = (1.0 / self.overround - 1.0) * 100.0;
+
+        // Non-positive odds would produce a meaningless (or negative) sum, never treat those as arbitrage.
+        self.is_arbitrage = self.effective_kof1 > 0.0
+            && self.effective_kof_draw > 0.0
+            && self.effective_kof2 > 0.0
+            && self.overround < 1.0;
+    }
+}
+
+/// Whether `a` and `b` are the same fixture, however each of their providers spells the teams.
+pub fn same_fixture(a: &PossibleArbitrage, b: &PossibleArbitrage) -> bool {
+    a.events.iter().any(|a_event| {
+        b.events
+            .iter()
+            .any(|b_event| compare_events(a_event, b_event).0)
+    })
+}
+
+/// Identifies an arbitrage by its fixture and the provider offering each outcome. Team names are
+/// sanitized and ordered alphabetically, so the id doesn't depend on which provider's naming or team
+/// order the match happened to take, e.g. `galatasaray|žalgiris|topSport|cBet|topSport` with the
+/// providers of the first team's win, the draw and the second team's win.
+fn arbitrage_id(arbitrage: &PossibleArbitrage) -> String {
+    let team1 = sanitize_team_name(arbitrage.team1.clone());
+    let team2 = sanitize_team_name(arbitrage.team2.clone());
+
+    let (teams, providers) = if team1 <= team2 {
+        (
+            [team1, team2],
+            [
+                &arbitrage.kof1_provider,
+                &arbitrage.kof_draw_provider,
+                &arbitrage.kof2_provider,
+            ],
+        )
+    } else {
+        (
+            [team2, team1],
+            [
+                &arbitrage.kof2_provider,
+                &arbitrage.kof_draw_provider,
+                &arbitrage.kof1_provider,
+            ],
+        )
+    };
+
+    format!(
+        "{}|{}|{}|{}|{}",
+        teams[0], teams[1], providers[0], providers[1], providers[2]
+    )
+}
+
+fn group_events(
+    mut events_by_provider: Vec<Vec<SportEvent>>,
+) -> Result<Vec<Vec<SportEvent>>, Box<dyn Error>> {
+    let mut grouped_events: Vec<Vec<SportEvent>> = vec![];
+
+    while let Some(mut base_provider_events) = events_by_provider.pop() {
+        while let Some(base_sport_event) = base_provider_events.pop() {
+            let mut matching_events: Vec<SportEvent> = vec![];
+
+            for provider_events in events_by_provider.iter_mut() {
+                let mut found_matching_event: Option<(usize, bool)> = Option::None;
+                for (i, sport_event) in provider_events.iter().enumerate() {
+                    let (events_match, order_matches) =
+                        compare_events(&base_sport_event, sport_event);
+                    if events_match {
+                        found_matching_event = Option::Some((i, order_matches));
+                        break;
+                    }
+                }
+
+                if let Some((matching_event_index, order_matches)) = found_matching_event {
+                    let mut event = provider_events.remove(matching_event_index);
+                    if !order_matches {
+                        event.switch_teams();
+                    }
+
+                    matching_events.push(event);
+                }
+            }
+
+            matching_events.push(base_sport_event);
+
+            grouped_events.push(matching_events);
+        }
+    }
+
+    Ok(grouped_events)
+}
+
+fn compare_events(event_1: &SportEvent, event_2: &SportEvent) -> (bool, bool) {
+    let event_1_team_1 = sanitize_team_name(event_1.team1.clone());
+    let event_1_team_2 = sanitize_team_name(event_1.team2.clone());
+    let event_2_team_1 = sanitize_team_name(event_2.team1.clone());
+    let event_2_team_2 = sanitize_team_name(event_2.team2.clone());
+
+    let team_1_1_names_match = event_1_team_1 == event_2_team_1
+        || event_1_team_1.contains(&event_2_team_1)
+        || event_2_team_1.contains(&event_1_team_1);
+
+    let team_2_2_names_match = event_1_team_2 == event_2_team_2
+        || event_1_team_2.contains(&event_2_team_2)
+        || event_2_team_2.contains(&event_1_team_2);
+
+    if team_1_1_names_match && team_2_2_names_match {
+        return (true, true);
+    }
+
+    let team_1_2_names_match = event_1_team_1 == event_2_team_2
+        || event_1_team_1.contains(&event_2_team_2)
+        || event_2_team_2.contains(&event_1_team_1);
+
+    let team_2_1_names_match = event_1_team_2 == event_2_team_1
+        || event_1_team_2.contains(&event_2_team_1)
+        || event_2_team_1.contains(&event_1_team_2);
+
+    if team_1_2_names_match && team_2_1_names_match {
+        return (true, false);
+    }
+
+    (false, false)
+}
+
+fn sanitize_team_name(team: String) -> String {
+    let lowercase = team.to_lowercase();
+    let normalized = lowercase.nfc().collect::<String>();
+    let fc_removed = normalized.replace("fc", "").trim().to_string();
+    let fk_removed = fc_removed.replace("fk", "").trim().to_string();
+    fk_removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fees::ProviderFees,
+        test_support::{assert_close, event, named_event},
+    };
+
+    #[test]
+    fn detects_arbitrage_from_best_odds() {
+        let events = vec![
+            vec![event("topSport", 2.2, 3.0, 3.0)],
+            vec![event("betSafe", 1.8, 4.0, 4.5)],
+        ];
+
+        let (arbitrages, unmatched) = find_arbitrages(events, &Fees::default()).unwrap();
+
+        assert!(unmatched.is_empty());
+        let arbitrage = &arbitrages[0];
+        assert_eq!(arbitrage.kof1_provider, "topSport");
+        assert_eq!(arbitrage.kof_draw_provider, "betSafe");
+        assert_eq!(arbitrage.kof2_provider, "betSafe");
+        assert_eq!(arbitrage.kof2, 4.5);
+        assert_close(arbitrage.overround, 1.0 / 2.2 + 1.0 / 4.0 + 1.0 / 4.5);
+        assert_close(
+            arbitrage.guaranteed_return,
+            (1.0 / arbitrage.overround - 1.0) * 100.0,
+        );
+        assert!(arbitrage.margin < 0.0);
+        assert!(arbitrage.is_arbitrage);
+    }
+
+    #[test]
+    fn identifies_arbitrage_by_teams_and_providers() {
+        let events = vec![
+            vec![named_event(
+                "topSport",
+                "FK Žalgiris",
+                "Galatasaray",
+                [5.5, 3.9, 2.5],
+            )],
+            vec![named_event(
+                "betSafe",
+                "Galatasaray",
+                "Žalgiris",
+                [2.6, 3.85, 4.6],
+            )],
+        ];
+
+        let (arbitrages, _) = find_arbitrages(events, &Fees::default()).unwrap();
+
+        // Galatasaray's win at betSafe, the draw and Žalgiris' win at topSport
+        assert_eq!(
+            arbitrages[0].id,
+            "galatasaray|žalgiris|betSafe|topSport|topSport"
+        );
+    }
+
+    #[test]
+    fn rejects_overround_of_at_least_one() {
+        let events = vec![
+            vec![event("topSport", 2.0, 3.0, 4.0)],
+            vec![event("betSafe", 2.0, 3.0, 4.0)],
+        ];
+
+        let (arbitrages, _) = find_arbitrages(events, &Fees::default()).unwrap();
+
+        assert!(arbitrages[0].overround >= 1.0);
+        assert!(!arbitrages[0].is_arbitrage);
+    }
+
+    #[test]
+    fn rejects_non_positive_odds() {
+        let events = vec![
+            vec![event("topSport", -2.0, 5.0, -2.0)],
+            vec![event("betSafe", -2.0, 5.0, -2.0)],
+        ];
+
+        let (arbitrages, _) = find_arbitrages(events, &Fees::default()).unwrap();
+
+        assert!(arbitrages[0].overround < 1.0);
+        assert!(!arbitrages[0].is_arbitrage);
+    }
+
+    #[test]
+    fn picks_best_odds_after_fees() {
+        let mut fees = Fees::default();
+        fees.providers.insert(
+            String::from("betSafe"),
+            ProviderFees {
+                winnings_tax: 50.0,
+                ..ProviderFees::default()
+            },
+        );
+        let events = vec![
+            vec![event("topSport", 2.2, 3.0, 2.5)],
+            vec![event("betSafe", 1.8, 4.0, 4.5)],
+        ];
+
+        let (arbitrages, _) = find_arbitrages(events, &fees).unwrap();
+
+        let arbitrage = &arbitrages[0];
+        assert_eq!(arbitrage.kof_draw_provider, "topSport");
+        assert_eq!(arbitrage.kof2_provider, "betSafe");
+        assert_close(arbitrage.effective_kof2, 2.75);
+        assert!(!arbitrage.is_arbitrage);
+    }
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, error::Error};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    arbitrage::{find_arbitrages, same_fixture, PossibleArbitrage},
+    fees::Fees,
+    models::SportEvent,
+    movements::Outcome,
+    stakes::StakePlanner,
+    storage::Scrape,
+};
+
+/// How arbitrages are picked and placed in a backtest.
+pub struct Strategy {
+    /// Smallest guaranteed return, in percent, worth placing.
+    pub min_profit: f64,
+    /// Amount split across the legs of every arbitrage taken.
+    pub stake: f64,
+    /// Time between spotting an arbitrage and having every leg placed.
+    pub latency: Duration,
+}
+
+/// Arbitrage the strategy would have placed.
+pub struct TakenArbitrage {
+    pub taken_at: DateTime<Utc>,
+    pub team1: String,
+    pub team2: String,
+    pub guaranteed_return: f64,
+    pub total_cost: f64,
+    pub guaranteed_profit: f64,
+    /// Leg whose price got worse, or disappeared, before the latency passed.
+    pub invalidated_by: Option<(Outcome, String)>,
+}
+
+pub struct BacktestReport {
+    pub scrapes: usize,
+    pub taken: Vec<TakenArbitrage>,
+    /// Arbitrages good enough to take that stake limits or rounding never let through.
+    pub skipped: usize,
+}
+
+impl BacktestReport {
+    pub fn invalidated(&self) -> usize {
+        self.taken
+            .iter()
+            .filter(|taken| taken.invalidated_by.is_some())
+            .count()
+    }
+
+    pub fn total_cost(&self) -> f64 {
+        self.taken
+            .iter()
+            .fold(0.0, |sum, taken| sum + taken.total_cost)
+    }
+
+    pub fn expected_profit(&self) -> f64 {
+        self.taken
+            .iter()
+            .fold(0.0, |sum, taken| sum + taken.guaranteed_profit)
+    }
+
+    /// Expected profit of the arbitrages whose prices all held until placed.
+    pub fn valid_profit(&self) -> f64 {
+        self.taken
+            .iter()
+            .filter(|taken| taken.invalidated_by.is_none())
+            .fold(0.0, |sum, taken| sum + taken.guaranteed_profit)
+    }
+}
+
+/// Walks `scrapes`, ordered by time, matching every provider's latest odds after each of them the
+/// way `watch` would, a failed provider's odds no longer taking part. Each fixture is taken at most
+/// once, the first time its arbitrage qualifies and can be planned, whichever providers offer it.
+/// A taken arbitrage counts as invalidated when any leg's provider offers a worse price for it, or
+/// no price at all, in its last scrape before the latency passed.
+pub fn backtest(
+    scrapes: &[Scrape],
+    fees: &Fees,
+    stake_planner: &StakePlanner,
+    strategy: &Strategy,
+) -> Result<BacktestReport, Box<dyn Error>> {
+    let mut scrapes_by_provider: HashMap<&str, Vec<&Scrape>> = HashMap::new();
+    for scrape in scrapes {
+        scrapes_by_provider
+            .entry(&scrape.provider)
+            .or_default()
+            .push(scrape);
+    }
+
+    // Providers are compared in the order they first show up, keeping the matching reproducible
+    let mut latest_events: Vec<(&str, Option<&[SportEvent]>)> = vec![];
+    let mut taken_arbitrages: Vec<PossibleArbitrage> = vec![];
+    let mut skipped_arbitrages: Vec<PossibleArbitrage> = vec![];
+    let mut taken = vec![];
+
+    for scrape in scrapes {
+        // Stale odds of a failing provider would report arbitrages `watch` drops
+        let events = match scrape.error {
+            None => Some(scrape.events.as_slice()),
+            Some(_) => None,
+        };
+        match latest_events
+            .iter_mut()
+            .find(|(provider, _)| *provider == scrape.provider)
+        {
+            Some((_, latest)) => *latest = events,
+            None => latest_events.push((&scrape.provider, events)),
+        }
+
+        let events_by_provider = latest_events
+            .iter()
+            .filter_map(|(_, events)| events.map(|events| events.to_vec()))
+            .collect::<Vec<_>>();
+        if events_by_provider.len() < 2 {
+            continue;
+        }
+
+        let (arbitrages, _) = find_arbitrages(events_by_provider, fees)?;
+
+        for arbitrage in arbitrages {
+            if !arbitrage.is_arbitrage
+                || arbitrage.guaranteed_return < strategy.min_profit
+                || taken_arbitrages
+                    .iter()
+                    .any(|taken| same_fixture(taken, &arbitrage))
+            {
+                continue;
+            }
+
+            let plan = match stake_planner.plan(&arbitrage, strategy.stake) {
+                Ok(plan) => plan,
+                Err(_) => {
+                    if !skipped_arbitrages
+                        .iter()
+                        .any(|skipped| same_fixture(skipped, &arbitrage))
+                    {
+                        skipped_arbitrages.push(arbitrage);
+                    }
+                    continue;
+                }
+            };
+
+            let placed_at = scrape.scraped_at + strategy.latency;
+            taken.push(TakenArbitrage {
+                taken_at: scrape.scraped_at,
+                team1: arbitrage.team1.clone(),
+                team2: arbitrage.team2.clone(),
+                guaranteed_return: arbitrage.guaranteed_return,
+                total_cost: plan.total_cost,
+                guaranteed_profit: plan.guaranteed_profit,
+                invalidated_by: invalidated_leg(&arbitrage, &scrapes_by_provider, placed_at),
+            });
+            skipped_arbitrages.retain(|skipped| !same_fixture(skipped, &arbitrage));
+            taken_arbitrages.push(arbitrage);
+        }
+    }
+
+    Ok(BacktestReport {
+        scrapes: scrapes.len(),
+        taken,
+        skipped: skipped_arbitrages.len(),
+    })
+}
+
+fn invalidated_leg(
+    arbitrage: &PossibleArbitrage,
+    scrapes_by_provider: &HashMap<&str, Vec<&Scrape>>,
+    placed_at: DateTime<Utc>,
+) -> Option<(Outcome, String)> {
+    let legs = [
+        (Outcome::Team1, &arbitrage.kof1_provider, arbitrage.kof1),
+        (
+            Outcome::Draw,
+            &arbitrage.kof_draw_provider,
+            arbitrage.kof_draw,
+        ),
+        (Outcome::Team2, &arbitrage.kof2_provider, arbitrage.kof2),
+    ];
+
+    for (outcome, provider, kof) in legs {
+        // Bookies name teams differently, the leg is looked up by its own provider's names
+        let leg_event = arbitrage
+            .events
+            .iter()
+            .find(|event| &event.provider == provider)?;
+        let price_at_placement = scrapes_by_provider
+            .get(provider.as_str())
+            .and_then(|scrapes| {
+                let placed = scrapes.partition_point(|scrape| scrape.scraped_at <= placed_at);
+                scrapes[..placed].last()
+            })
+            .and_then(|scrape| price(&scrape.events, leg_event, outcome));
+
+        if price_at_placement.is_none_or(|price| price < kof) {
+            return Some((outcome, provider.clone()));
+        }
+    }
+
+    None
+}
+
+/// Price of `outcome` of `leg_event` among `events` scraped from the same provider, which list the
+/// teams in the provider's own order while `leg_event` may have had them switched for matching.
+fn price(events: &[SportEvent], leg_event: &SportEvent, outcome: Outcome) -> Option<f64> {
+    events.iter().find_map(|event| {
+        let in_order = event.team1 == leg_event.team1 && event.team2 == leg_event.team2;
+        let switched = event.team1 == leg_event.team2 && event.team2 == leg_event.team1;
+        match (outcome, in_order, switched) {
+            (Outcome::Draw, true, _) | (Outcome::Draw, _, true) => Some(event.kof_draw),
+            (Outcome::Team1, true, _) | (Outcome::Team2, _, true) => Some(event.kof1),
+            (Outcome::Team2, true, _) | (Outcome::Team1, _, true) => Some(event.kof2),
+            _ => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stakes::StakeRounding,
+        test_support::{assert_close, at, scrape},
+    };
+
+    fn run(scrapes: &[Scrape], latency: i64) -> BacktestReport {
+        let planner = StakePlanner::new(StakeRounding::new(0.0), Fees::default());
+        let strategy = Strategy {
+            min_profit: 0.0,
+            stake: 100.0,
+            latency: Duration::seconds(latency),
+        };
+
+        backtest(scrapes, &Fees::default(), &planner, &strategy).unwrap()
+    }
+
+    #[test]
+    fn takes_arbitrage_once() {
+        let scrapes = [
+            scrape("topSport", 0, Some([2.2, 3.0, 3.0])),
+            scrape("betSafe", 10, Some([1.8, 4.0, 4.5])),
+            scrape("topSport", 20, Some([2.2, 3.0, 3.0])),
+        ];
+
+        let report = run(&scrapes, 0);
+
+        assert_eq!(report.scrapes, 3);
+        assert_eq!(report.taken.len(), 1);
+        let taken = &report.taken[0];
+        assert_eq!(taken.taken_at, at(10));
+        assert_close(taken.total_cost, 100.0);
+        assert_close(taken.guaranteed_profit, 100.0 / 0.9267676767676768 - 100.0);
+        assert!(taken.invalidated_by.is_none());
+        assert_eq!(report.invalidated(), 0);
+        assert_close(report.valid_profit(), taken.guaranteed_profit);
+    }
+
+    #[test]
+    fn takes_fixture_once_when_best_price_moves_to_another_provider() {
+        let scrapes = [
+            scrape("topSport", 0, Some([2.2, 3.0, 3.0])),
+            scrape("betSafe", 10, Some([1.8, 4.0, 4.5])),
+            scrape("topSport", 20, Some([2.2, 3.0, 5.0])),
+        ];
+
+        let report = run(&scrapes, 0);
+
+        assert_eq!(report.taken.len(), 1);
+        assert_eq!(report.taken[0].taken_at, at(10));
+    }
+
+    #[test]
+    fn invalidates_arbitrage_whose_price_drops_before_placing() {
+        let scrapes = [
+            scrape("topSport", 0, Some([2.2, 3.0, 3.0])),
+            scrape("betSafe", 10, Some([1.8, 4.0, 4.5])),
+            scrape("betSafe", 20, Some([1.8, 4.0, 4.0])),
+        ];
+
+        let report = run(&scrapes, 30);
+
+        assert_eq!(report.taken.len(), 1);
+        assert_eq!(
+            report.taken[0].invalidated_by,
+            Some((Outcome::Team2, String::from("betSafe")))
+        );
+        assert_eq!(report.invalidated(), 1);
+        assert_eq!(report.valid_profit(), 0.0);
+    }
+
+    #[test]
+    fn drops_odds_of_failed_provider() {
+        let scrapes = [
+            scrape("betSafe", 0, Some([1.8, 4.0, 4.5])),
+            scrape("betSafe", 10, None),
+            scrape("topSport", 20, Some([2.2, 3.0, 3.0])),
+        ];
+
+        let report = run(&scrapes, 0);
+
+        assert!(report.taken.is_empty());
+    }
+}
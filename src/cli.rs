@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use crate::config::DEFAULT_CONFIG_PATH;
+
+/// Gathers bet ratios from lithuanian bet sites and finds arbitrage bets.
+#[derive(Parser)]
+pub struct Cli {
+    /// Configuration file with providers, pages and stake settings.
+    #[arg(long, global = true, default_value = DEFAULT_CONFIG_PATH)]
+    pub config: String,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Scrapes every provider once and reports arbitrages.
+    Scan {
+        #[command(flatten)]
+        scrape: ScrapeArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// Also saves the results to this file so they can be rendered later with `report`.
+        #[arg(long)]
+        save: Option<PathBuf>,
+    },
+    /// Keeps re-parsing every provider on its own schedule and reports arbitrages as they appear or change.
+    Watch {
+        #[command(flatten)]
+        scrape: ScrapeArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// Seconds between parses of providers without a `refresh_interval` of their own.
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+        /// Serves the latest odds, arbitrages and provider health as JSON on this address, e.g.
+        /// 127.0.0.1:8080.
+        #[arg(long)]
+        listen: Option<String>,
+    },
+    /// Renders results saved with `scan --save`.
+    Report {
+        input: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Re-runs matching over pages recorded with `--record`, reporting arbitrages as they appeared.
+    Replay {
+        /// Directory passed to `--record`.
+        recording: PathBuf,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Lists odds movements between consecutive scrapes kept in the storage database.
+    Movements {
+        /// Only movements of this provider.
+        #[arg(long)]
+        provider: Option<String>,
+        /// Only scrapes since this time, e.g. 2024-08-01T18:00:00Z.
+        #[arg(long)]
+        since: Option<DateTime<Utc>>,
+        /// Only movements beyond the configured `sharp_move`.
+        #[arg(long)]
+        sharp: bool,
+    },
+    /// Emails a summary of a day's arbitrages, matching and provider failures from the database.
+    Digest {
+        /// Day (UTC) to sum up, yesterday by default.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+        /// Prints the digest instead of sending it.
+        #[arg(long)]
+        print: bool,
+    },
+    /// Simulates placing arbitrages found in the stored odds history.
+    Backtest {
+        /// Only odds scraped since this time, e.g. 2024-08-01T00:00:00Z.
+        #[arg(long)]
+        from: Option<DateTime<Utc>>,
+        /// Only odds scraped before this time.
+        #[arg(long)]
+        to: Option<DateTime<Utc>>,
+        /// Only takes arbitrages guaranteeing at least this return, in percent.
+        #[arg(long, default_value_t = 0.0)]
+        min_profit: f64,
+        /// Amount staked on every arbitrage, the configured `stakes.total` by default.
+        #[arg(long)]
+        stake: Option<f64>,
+        /// Seconds it takes to place every leg once an arbitrage is spotted.
+        #[arg(long, default_value_t = 0)]
+        latency: u64,
+    },
+}
+
+#[derive(Args)]
+pub struct ScrapeArgs {
+    /// Comma separated ids of the providers to scrape, every enabled provider by default.
+    #[arg(long, value_delimiter = ',')]
+    pub providers: Vec<String>,
+    /// Reads pages saved under this file or directory instead of the live sites.
+    #[arg(long)]
+    pub snapshots: Option<String>,
+    /// Also saves every fetched page under this directory, the session can be re-run with `replay`.
+    #[arg(long)]
+    pub record: Option<String>,
+}
+
+#[derive(Args)]
+pub struct OutputArgs {
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+    /// Only reports arbitrages guaranteeing at least this return, in percent.
+    #[arg(long, default_value_t = 0.0)]
+    pub min_profit: f64,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    /// Stake plans of the found arbitrages.
+    Text,
+    /// Every matched fixture sorted by profitability, arbitrages highlighted.
+    Table,
+    /// A single JSON document with arbitrages, unmatched events and per-provider counts.
+    Json,
+    /// One JSON object per line, tagged with its `type`.
+    Jsonl,
+    /// Odds comparison grid of every matched event, comma separated. While watching, rows of the
+    /// arbitrages that appear or change are added under a single header.
+    Csv,
+    /// Same as csv, tab separated.
+    Tsv,
+    /// Everything that was scraped and matched, in Rust debug notation.
+    Debug,
+}
@@ -0,0 +1,269 @@
+use std::{collections::HashMap, error::Error, fs};
+
+use serde::Deserialize;
+
+use crate::{
+    fees::{Fees, ProviderFees},
+    stakes::{StakeLimits, StakePlanner, StakeRounding},
+};
+
+pub const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub stakes: StakesConfig,
+    /// Database every scrape and detected arbitrage is recorded to, nothing is kept when not set.
+    pub storage: Option<StorageConfig>,
+    #[serde(default)]
+    pub movements: MovementsConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    pub providers: Vec<ProviderConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct StakesConfig {
+    /// Amount, fees included, split across the legs of an arbitrage.
+    pub total: f64,
+    /// Increment stakes are rounded to unless the provider overrides it.
+    pub increment: f64,
+}
+
+impl Default for StakesConfig {
+    fn default() -> Self {
+        StakesConfig {
+            total: 100.0,
+            increment: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StorageConfig {
+    /// SQLite database file, created on first use.
+    pub path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct MovementsConfig {
+    /// Change of a price, in percent, from which its movement is flagged as sharp.
+    pub sharp_move: f64,
+}
+
+impl Default for MovementsConfig {
+    fn default() -> Self {
+        MovementsConfig { sharp_move: 5.0 }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct NotificationsConfig {
+    /// Arbitrages are announced once they guarantee at least this return, in percent.
+    pub min_profit: f64,
+    /// Times a failed notification is retried before giving up on it.
+    pub retries: u32,
+    pub webhooks: Vec<WebhookConfig>,
+    pub telegram: Option<TelegramConfig>,
+    /// Where `digest` mails its daily summary.
+    pub email: Option<EmailConfig>,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        NotificationsConfig {
+            min_profit: 0.0,
+            retries: 3,
+            webhooks: vec![],
+            telegram: None,
+            email: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub format: WebhookFormat,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// `{"text": ...}` message for Slack incoming webhooks.
+    Slack,
+    /// `{"content": ...}` message for Discord webhooks.
+    Discord,
+    /// The arbitrages as JSON, the same document `--format json` prints while watching.
+    #[default]
+    Generic,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TelegramConfig {
+    /// Bot token issued by BotFather.
+    pub token: String,
+    /// Chat, group or `@channel` the bot posts to.
+    pub chat_id: String,
+    /// Bot API base url, pointed elsewhere to use a stand-in.
+    #[serde(default = "default_telegram_api_url")]
+    pub api_url: String,
+    /// Message for a single arbitrage. `{team1}`, `{team2}`, `{return}`, `{kof1}`, `{kof_draw}`,
+    /// `{kof2}`, their `{..._provider}`, `{stake1}`, `{stake_draw}`, `{stake2}`, `{total_stake}`
+    /// and `{profit}` are filled in.
+    #[serde(default = "default_telegram_template")]
+    pub template: String,
+    /// Seconds between two messages, arbitrages found in between are collapsed into the next one.
+    #[serde(default = "default_telegram_min_interval")]
+    pub min_interval: u64,
+    /// Arbitrages spelled out in one message, any further ones are only counted.
+    #[serde(default = "default_telegram_max_arbitrages")]
+    pub max_arbitrages: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailConfig {
+    /// SMTP server.
+    pub host: String,
+    /// Port of the server when it isn't the default one for `security`.
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain connection, only meant for local servers.
+    None,
+    #[default]
+    StartTls,
+    Tls,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProviderConfig {
+    pub id: String,
+    pub platform: Platform,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub fetch: FetchMode,
+    /// Competition pages scraped for this provider.
+    pub pages: Vec<String>,
+    /// Seconds between parses in watch mode, the `--interval` passed to watch when not set.
+    pub refresh_interval: Option<u64>,
+    pub stake_increment: Option<f64>,
+    #[serde(default)]
+    pub limits: StakeLimits,
+    #[serde(default)]
+    pub fees: ProviderFees,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    TopSport,
+    WptTable,
+    OlyBet,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FetchMode {
+    #[default]
+    Http,
+    Browser,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_telegram_api_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+fn default_telegram_template() -> String {
+    "{team1} - {team2}: {return}% guaranteed return
+1 @ {kof1} on {kof1_provider}, stake {stake1}
+X @ {kof_draw} on {kof_draw_provider}, stake {stake_draw}
+2 @ {kof2} on {kof2_provider}, stake {stake2}
+staked {total_stake}, profit {profit}"
+        .to_string()
+}
+
+fn default_telegram_min_interval() -> u64 {
+    30
+}
+
+fn default_telegram_max_arbitrages() -> usize {
+    5
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read config {}: {}", path, e))?;
+
+        toml::from_str(&content).map_err(|e| format!("invalid config {}: {}", path, e).into())
+    }
+
+    /// Providers with the given ids, every enabled provider when `ids` is empty.
+    pub fn selected_providers(
+        &self,
+        ids: &[String],
+    ) -> Result<Vec<&ProviderConfig>, Box<dyn Error>> {
+        if ids.is_empty() {
+            return Ok(self
+                .providers
+                .iter()
+                .filter(|provider| provider.enabled)
+                .collect());
+        }
+
+        ids.iter()
+            .map(|id| {
+                self.providers
+                    .iter()
+                    .find(|provider| &provider.id == id)
+                    .ok_or_else(|| format!("provider {} isn't configured", id).into())
+            })
+            .collect()
+    }
+
+    pub fn fees(&self) -> Fees {
+        Fees {
+            providers: self
+                .providers
+                .iter()
+                .map(|provider| (provider.id.clone(), provider.fees.clone()))
+                .collect(),
+        }
+    }
+
+    pub fn stake_planner(&self) -> StakePlanner {
+        let mut rounding = StakeRounding::new(self.stakes.increment);
+        let mut limits = HashMap::new();
+        for provider in &self.providers {
+            if let Some(increment) = provider.stake_increment {
+                rounding
+                    .provider_increments
+                    .insert(provider.id.clone(), increment);
+            }
+            limits.insert(provider.id.clone(), provider.limits.clone());
+        }
+
+        let mut planner = StakePlanner::new(rounding, self.fees());
+        planner.limits = limits;
+        planner
+    }
+}
@@ -0,0 +1,10 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait ContentSource: Send + Sync {
+    /// Returns the HTML of `url`. `ready_selector` matches an element that has to be present before
+    /// a dynamically rendered page counts as loaded, sources serving static content ignore it.
+    async fn fetch(&self, url: &str, ready_selector: &str) -> Result<String, Box<dyn Error>>;
+}
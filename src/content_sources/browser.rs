@@ -0,0 +1,33 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use playwright::api::Page;
+
+use crate::content_source::ContentSource;
+
+pub struct BrowserSource {
+    page: Page,
+}
+
+impl BrowserSource {
+    pub fn new(page: Page) -> Self {
+        BrowserSource { page }
+    }
+}
+
+#[async_trait]
+impl ContentSource for BrowserSource {
+    async fn fetch(&self, url: &str, ready_selector: &str) -> Result<String, Box<dyn Error>> {
+        self.page
+            .goto_builder(url)
+            // .wait_until(playwright::api::DocumentLoadState::DomContentLoaded)
+            .goto()
+            .await?;
+
+        self.page
+            .wait_for_selector_builder(ready_selector)
+            .wait_for_selector()
+            .await?;
+        Ok(self.page.content().await?)
+    }
+}
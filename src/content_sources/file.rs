@@ -0,0 +1,51 @@
+use std::{error::Error, fs, path::PathBuf};
+
+use async_trait::async_trait;
+
+use crate::content_source::ContentSource;
+
+/// Serves pages from disk. Pointed at a single file it returns that file for every url, pointed at a
+/// directory it returns `<directory>/<snapshot_file_name(url)>`, the layout snapshots are stored in.
+pub struct FileSource {
+    path: PathBuf,
+}
+
+impl FileSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileSource { path: path.into() }
+    }
+
+    fn file_for(&self, url: &str) -> PathBuf {
+        if self.path.is_dir() {
+            self.path.join(snapshot_file_name(url))
+        } else {
+            self.path.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl ContentSource for FileSource {
+    async fn fetch(&self, url: &str, _ready_selector: &str) -> Result<String, Box<dyn Error>> {
+        let file = self.file_for(url);
+        fs::read_to_string(&file)
+            .map_err(|e| format!("couldn't read {} for {}: {}", file.display(), url, e).into())
+    }
+}
+
+/// Turns a url into a file name that is stable and valid on every platform.
+pub fn snapshot_file_name(url: &str) -> String {
+    let without_scheme = url.split("://").last().unwrap_or(url);
+    let sanitized = without_scheme
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    format!("{}.html", sanitized.trim_matches('_'))
+}
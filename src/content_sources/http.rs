@@ -0,0 +1,36 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+
+use crate::content_source::ContentSource;
+
+use super::http_client_extensions::DefaultChromeHeaders;
+
+pub struct HttpSource {
+    client: reqwest::Client,
+}
+
+impl HttpSource {
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Ok(HttpSource {
+            client: reqwest::Client::builder().gzip(true).brotli(true).build()?,
+        })
+    }
+}
+
+#[async_trait]
+impl ContentSource for HttpSource {
+    async fn fetch(&self, url: &str, _ready_selector: &str) -> Result<String, Box<dyn Error>> {
+        let resp = self
+            .client
+            .get(url)
+            .default_chrome_headers()
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Ok(resp)
+    }
+}
@@ -0,0 +1,77 @@
+use std::{error::Error, fs, path::PathBuf, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use crate::{content_source::ContentSource, content_sources::file::snapshot_file_name};
+
+/// Name of the directory a single parse of a provider is recorded to, the time its first page was
+/// fetched in UTC.
+pub const RUN_DIR_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// File in a run directory holding the error fetching one of its pages failed with, which ended
+/// the parse.
+pub const ERROR_FILE_NAME: &str = "error.txt";
+
+/// Passes pages through from another source while saving them under
+/// `<directory>/<RUN_DIR_FORMAT>/<snapshot_file_name(url)>`, so every recorded parse can be read back
+/// with a [`FileSource`](crate::content_sources::file::FileSource). A parse always starts by
+/// fetching the provider's first page, which is what starts a new run directory. A failed fetch is
+/// recorded as `ERROR_FILE_NAME`, so replays drop the provider's odds the way the live run did.
+pub struct RecordingSource {
+    source: Box<dyn ContentSource>,
+    directory: PathBuf,
+    first_page: String,
+    run_directory: Mutex<PathBuf>,
+}
+
+impl RecordingSource {
+    pub fn new(
+        source: Box<dyn ContentSource>,
+        directory: impl Into<PathBuf>,
+        first_page: &str,
+    ) -> Self {
+        let directory = directory.into();
+        RecordingSource {
+            source,
+            run_directory: Mutex::new(directory.clone()),
+            directory,
+            first_page: first_page.to_string(),
+        }
+    }
+
+    fn record(
+        &self,
+        url: &str,
+        fetched: &Result<String, Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut run_directory = self.run_directory.lock().map_err(|e| e.to_string())?;
+        if url == self.first_page {
+            *run_directory = self
+                .directory
+                .join(Utc::now().format(RUN_DIR_FORMAT).to_string());
+            fs::create_dir_all(run_directory.as_path())
+                .map_err(|e| format!("couldn't create {}: {}", run_directory.display(), e))?;
+        }
+
+        let (file, content) = match fetched {
+            Ok(html) => (run_directory.join(snapshot_file_name(url)), html.clone()),
+            Err(e) => (run_directory.join(ERROR_FILE_NAME), e.to_string()),
+        };
+        fs::write(&file, content)
+            .map_err(|e| format!("couldn't record {} to {}: {}", url, file.display(), e).into())
+    }
+}
+
+#[async_trait]
+impl ContentSource for RecordingSource {
+    async fn fetch(&self, url: &str, ready_selector: &str) -> Result<String, Box<dyn Error>> {
+        let fetched = self.source.fetch(url, ready_selector).await;
+        // A recording problem mustn't cost the live run its odds
+        if let Err(e) = self.record(url, &fetched) {
+            eprintln!("{}", e);
+        }
+
+        fetched
+    }
+}
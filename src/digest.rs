@@ -0,0 +1,74 @@
+use std::error::Error;
+
+use chrono::{Days, NaiveDate};
+
+use crate::storage::{ArbitrageSummary, FailureSummary, MatchingSummary, Storage};
+
+/// What was found during a single day (UTC), for those not following the live alerts.
+pub struct Digest {
+    pub day: NaiveDate,
+    pub arbitrages: Vec<ArbitrageSummary>,
+    pub failures: Vec<FailureSummary>,
+    pub matching: MatchingSummary,
+}
+
+impl Digest {
+    pub fn load(storage: &Storage, day: NaiveDate) -> Result<Digest, Box<dyn Error>> {
+        let since = day.and_hms_opt(0, 0, 0).ok_or("invalid day")?.and_utc();
+        let until = since.checked_add_days(Days::new(1)).ok_or("invalid day")?;
+
+        Ok(Digest {
+            day,
+            arbitrages: storage.arbitrage_summaries(since, until)?,
+            failures: storage.failure_summaries(since, until)?,
+            matching: storage.matching_summary(since, until)?,
+        })
+    }
+
+    pub fn subject(&self) -> String {
+        format!(
+            "Arbitrage digest for {}: {} arbitrages",
+            self.day,
+            self.arbitrages.len()
+        )
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![format!("Arbitrages found on {} (UTC):", self.day)];
+        if self.arbitrages.is_empty() {
+            lines.push("  none".to_string());
+        }
+        for arbitrage in &self.arbitrages {
+            lines.push(format!(
+                "  {} - {}: up to {:.2}% guaranteed return, first seen {}, appeared {} times, lasting up to {}s",
+                arbitrage.team1,
+                arbitrage.team2,
+                arbitrage.best_return,
+                arbitrage.first_seen.format("%Y-%m-%d %H:%M:%S"),
+                arbitrage.lifecycles,
+                arbitrage.longest_duration
+            ));
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "Odds were matched {} times, leaving {:.1} events without a counterpart on average and {} at most.",
+            self.matching.matchings,
+            self.matching.average_unmatched_events,
+            self.matching.max_unmatched_events
+        ));
+
+        if !self.failures.is_empty() {
+            lines.push(String::new());
+            lines.push("Provider failures:".to_string());
+        }
+        for failure in &self.failures {
+            lines.push(format!(
+                "  {}: {} of {} scrapes failed, last with: {}",
+                failure.provider, failure.failures, failure.scrapes, failure.last_error
+            ));
+        }
+
+        lines.join("\n") + "\n"
+    }
+}
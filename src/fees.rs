@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProviderFees {
+    /// Percentage withheld from net winnings (payout minus stake).
+    pub winnings_tax: f64,
+    /// Percentage of the stake charged on top of it.
+    pub stake_fee: f64,
+    /// Fixed amount charged for every placed bet.
+    pub flat_fee: f64,
+}
+
+impl ProviderFees {
+    /// What a winning bet pays out after winnings tax.
+    pub fn payout(&self, stake: f64, kof: f64) -> f64 {
+        stake + stake * (kof - 1.0) * (1.0 - self.winnings_tax / 100.0)
+    }
+
+    /// What placing a bet costs including the stake fee and flat fee.
+    pub fn cost(&self, stake: f64) -> f64 {
+        stake * (1.0 + self.stake_fee / 100.0) + self.flat_fee
+    }
+
+    /// Odds expressed as payout per unit spent, with percentage based deductions applied.
+    /// Flat fees depend on the stake size and are only accounted for when planning stakes.
+    pub fn effective_kof(&self, kof: f64) -> f64 {
+        self.payout(1.0, kof) / (1.0 + self.stake_fee / 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Fees {
+    pub providers: HashMap<String, ProviderFees>,
+}
+
+impl Fees {
+    pub fn for_provider(&self, provider: &str) -> ProviderFees {
+        self.providers.get(provider).cloned().unwrap_or_default()
+    }
+
+    pub fn effective_kof(&self, provider: &str, kof: f64) -> f64 {
+        match self.providers.get(provider) {
+            Some(fees) => fees.effective_kof(kof),
+            None => kof,
+        }
+    }
+}
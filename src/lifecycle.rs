@@ -0,0 +1,337 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arbitrage::{same_fixture, PossibleArbitrage},
+    storage::Storage,
+};
+
+/// How long an arbitrage, identified by its `id`, has existed and how good it got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lifecycle {
+    pub id: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    /// Best guaranteed return seen, in percent.
+    pub peak_return: f64,
+    /// Whether its return reached the one notifications are sent from, it's announced only then.
+    pub announced: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Appeared,
+    /// Prices, and with them the guaranteed return, moved.
+    Changed,
+    Disappeared,
+}
+
+/// Arbitrage that appeared, changed or disappeared in an update.
+#[derive(Debug, Clone, Serialize)]
+pub struct ArbitrageChange {
+    pub change: ChangeKind,
+    pub timestamp: DateTime<Utc>,
+    pub lifecycle: Lifecycle,
+    /// Whether notifiers should hear about it, the first time its return is good enough.
+    pub announce: bool,
+    /// Latest data of the arbitrage, the last one seen when it disappeared.
+    pub arbitrage: PossibleArbitrage,
+}
+
+/// Keeps the lifecycle of every current arbitrage to tell what changed between updates.
+pub struct ArbitrageTracker {
+    current: HashMap<String, (Lifecycle, PossibleArbitrage)>,
+    /// Guaranteed return, in percent, from which arbitrages are announced.
+    announce_from: f64,
+    /// Providers yet to report since tracking started. An arbitrage missing before all of them have
+    /// may only be waiting for its providers, so updates are held back until then.
+    unreported: HashSet<String>,
+}
+
+impl ArbitrageTracker {
+    pub fn new(announce_from: f64) -> Self {
+        ArbitrageTracker {
+            current: HashMap::new(),
+            announce_from,
+            unreported: HashSet::new(),
+        }
+    }
+
+    /// Continues tracking arbitrages that were still around when a previous run stopped.
+    pub fn resume(mut self, current: Vec<(Lifecycle, PossibleArbitrage)>) -> Self {
+        self.current = current
+            .into_iter()
+            .map(|(lifecycle, arbitrage)| (lifecycle.id.clone(), (lifecycle, arbitrage)))
+            .collect();
+        self
+    }
+
+    /// Holds updates back until every one of `providers` has been `reported`.
+    pub fn awaiting<'a>(mut self, providers: impl IntoIterator<Item = &'a str>) -> Self {
+        self.unreported = providers.into_iter().map(String::from).collect();
+        self
+    }
+
+    /// Notes that `provider` reported, successfully or not. Tells whether every awaited provider has.
+    pub fn reported(&mut self, provider: &str) -> bool {
+        self.unreported.remove(provider);
+        self.unreported.is_empty()
+    }
+
+    /// Compares the arbitrages among `arbitrages` with the previous update. An arbitrage continues
+    /// the lifecycle of the same fixture and takes over its id, even when its own id differs since
+    /// the best prices moved to other providers. Whatever isn't among them anymore disappears and
+    /// starts a new lifecycle once it comes back. Nothing changes while awaited providers are yet
+    /// to report.
+    pub fn update(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        arbitrages: Vec<PossibleArbitrage>,
+    ) -> Vec<ArbitrageChange> {
+        if !self.unreported.is_empty() {
+            return vec![];
+        }
+
+        let mut current = HashMap::new();
+        let mut changes = vec![];
+        for mut arbitrage in arbitrages.into_iter().filter(|a| a.is_arbitrage) {
+            let known_id = if self.current.contains_key(&arbitrage.id) {
+                Some(arbitrage.id.clone())
+            } else {
+                self.current
+                    .iter()
+                    .find(|(_, (_, known))| same_fixture(known, &arbitrage))
+                    .map(|(id, _)| id.clone())
+            };
+            let known = known_id.and_then(|id| self.current.remove(&id));
+            let (change, mut lifecycle) = match known {
+                Some((lifecycle, known)) if same_prices(&known, &arbitrage) => (None, lifecycle),
+                Some((lifecycle, _)) => (Some(ChangeKind::Changed), lifecycle),
+                None => (
+                    Some(ChangeKind::Appeared),
+                    Lifecycle {
+                        id: arbitrage.id.clone(),
+                        first_seen: timestamp,
+                        last_seen: timestamp,
+                        peak_return: arbitrage.guaranteed_return,
+                        announced: false,
+                    },
+                ),
+            };
+            arbitrage.id = lifecycle.id.clone();
+            lifecycle.last_seen = timestamp;
+            lifecycle.peak_return = lifecycle.peak_return.max(arbitrage.guaranteed_return);
+
+            if let Some(change) = change {
+                let announce =
+                    !lifecycle.announced && arbitrage.guaranteed_return >= self.announce_from;
+                lifecycle.announced |= announce;
+
+                let mut change = ArbitrageChange::new(change, timestamp, &lifecycle, &arbitrage);
+                change.announce = announce;
+                changes.push(change);
+            }
+            current.insert(arbitrage.id.clone(), (lifecycle, arbitrage));
+        }
+
+        let disappeared = std::mem::replace(&mut self.current, current);
+        for (lifecycle, arbitrage) in disappeared.into_values() {
+            changes.push(ArbitrageChange::new(
+                ChangeKind::Disappeared,
+                timestamp,
+                &lifecycle,
+                &arbitrage,
+            ));
+        }
+
+        changes
+    }
+
+    pub fn current(&self) -> impl Iterator<Item = &(Lifecycle, PossibleArbitrage)> {
+        self.current.values()
+    }
+}
+
+impl ArbitrageChange {
+    fn new(
+        change: ChangeKind,
+        timestamp: DateTime<Utc>,
+        lifecycle: &Lifecycle,
+        arbitrage: &PossibleArbitrage,
+    ) -> Self {
+        ArbitrageChange {
+            change,
+            timestamp,
+            lifecycle: lifecycle.clone(),
+            announce: false,
+            arbitrage: arbitrage.clone(),
+        }
+    }
+}
+
+/// Tracks a single matching against the lifecycles left open in `storage` by previous runs, and
+/// records the arbitrages that appeared or changed and their lifecycles there. Arbitrages are
+/// announced from `announce_from` percent return.
+pub fn track_once(
+    storage: Option<&mut Storage>,
+    timestamp: DateTime<Utc>,
+    arbitrages: Vec<PossibleArbitrage>,
+    announce_from: f64,
+) -> Result<Vec<ArbitrageChange>, Box<dyn Error>> {
+    let Some(storage) = storage else {
+        return Ok(ArbitrageTracker::new(announce_from).update(timestamp, arbitrages));
+    };
+
+    let mut tracker = ArbitrageTracker::new(announce_from).resume(storage.open_lifecycles()?);
+    let changes = tracker.update(timestamp, arbitrages);
+    storage.save_arbitrages(timestamp, &changes)?;
+    storage.save_lifecycles(timestamp, &tracker, &changes)?;
+
+    Ok(changes)
+}
+
+fn same_prices(a: &PossibleArbitrage, b: &PossibleArbitrage) -> bool {
+    a.kof1 == b.kof1
+        && a.kof_draw == b.kof_draw
+        && a.kof2 == b.kof2
+        && a.kof1_provider == b.kof1_provider
+        && a.kof_draw_provider == b.kof_draw_provider
+        && a.kof2_provider == b.kof2_provider
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fees::Fees,
+        test_support::{self, at, event, named_event},
+    };
+
+    /// Arbitrage of topSport's and betSafe's odds, the draw and second team's win at betSafe.
+    fn arbitrage(bet_safe_kof2: f64) -> PossibleArbitrage {
+        test_support::arbitrage(
+            vec![
+                event("topSport", 2.2, 3.0, 3.0),
+                event("betSafe", 1.8, 4.0, bet_safe_kof2),
+            ],
+            &Fees::default(),
+        )
+    }
+
+    fn kinds(changes: &[ArbitrageChange]) -> Vec<ChangeKind> {
+        changes.iter().map(|change| change.change).collect()
+    }
+
+    #[test]
+    fn waits_for_every_provider_before_resumed_arbitrages_disappear() {
+        let arbitrage = arbitrage(4.5);
+        let lifecycle = Lifecycle {
+            id: arbitrage.id.clone(),
+            first_seen: at(0),
+            last_seen: at(10),
+            peak_return: arbitrage.guaranteed_return,
+            announced: true,
+        };
+        let mut tracker = ArbitrageTracker::new(0.0)
+            .awaiting(["topSport", "betSafe"])
+            .resume(vec![(lifecycle, arbitrage.clone())]);
+
+        assert!(!tracker.reported("topSport"));
+        assert!(tracker.update(at(20), vec![]).is_empty());
+
+        assert!(tracker.reported("betSafe"));
+        assert!(tracker.update(at(21), vec![arbitrage.clone()]).is_empty());
+        let (lifecycle, _) = tracker.current().next().unwrap();
+        assert_eq!(lifecycle.first_seen, at(0));
+        assert_eq!(lifecycle.last_seen, at(21));
+
+        let changes = tracker.update(at(30), vec![]);
+        assert_eq!(kinds(&changes), [ChangeKind::Disappeared]);
+        assert!(!changes[0].announce);
+    }
+
+    #[test]
+    fn announces_once_return_first_reaches_threshold() {
+        let low = arbitrage(4.5);
+        let high = arbitrage(6.0);
+        let higher = arbitrage(8.0);
+        assert!(low.guaranteed_return < 10.0 && high.guaranteed_return >= 10.0);
+        let mut tracker = ArbitrageTracker::new(10.0);
+
+        let changes = tracker.update(at(0), vec![low]);
+        assert_eq!(kinds(&changes), [ChangeKind::Appeared]);
+        assert!(!changes[0].announce);
+
+        let changes = tracker.update(at(10), vec![high]);
+        assert_eq!(kinds(&changes), [ChangeKind::Changed]);
+        assert!(changes[0].announce);
+        assert!(changes[0].lifecycle.announced);
+
+        let changes = tracker.update(at(20), vec![higher]);
+        assert_eq!(kinds(&changes), [ChangeKind::Changed]);
+        assert!(!changes[0].announce);
+    }
+
+    #[test]
+    fn keeps_lifecycle_when_best_price_moves_to_another_provider() {
+        let mut tracker = ArbitrageTracker::new(0.0);
+        let changes = tracker.update(at(0), vec![arbitrage(4.5)]);
+        assert!(changes[0].announce);
+
+        // topSport now beats betSafe's 4.5 for the second team's win
+        let moved = test_support::arbitrage(
+            vec![
+                event("topSport", 2.2, 3.0, 5.0),
+                event("betSafe", 1.8, 4.0, 4.5),
+            ],
+            &Fees::default(),
+        );
+        let changes = tracker.update(at(10), vec![moved]);
+
+        assert_eq!(kinds(&changes), [ChangeKind::Changed]);
+        assert_eq!(changes[0].arbitrage.kof2_provider, "topSport");
+        assert_eq!(changes[0].lifecycle.first_seen, at(0));
+        assert_eq!(changes[0].arbitrage.id, changes[0].lifecycle.id);
+        assert!(!changes[0].announce);
+    }
+
+    #[test]
+    fn keeps_lifecycle_when_provider_with_shortest_team_name_drops_out() {
+        let top_sport = named_event("topSport", "FK Žalgiris", "Galatasaray", [5.5, 3.9, 2.5]);
+        let c_bet = named_event(
+            "cBet",
+            "FK Žalgiris Vilnius",
+            "Galatasaray",
+            [4.75, 3.95, 2.6],
+        );
+        let oly_bet = named_event(
+            "olyBet",
+            "Žalgiris Vilnius",
+            "Galatasaray",
+            [5.0, 3.8, 2.55],
+        );
+        let all = test_support::arbitrage(
+            vec![top_sport, c_bet.clone(), oly_bet.clone()],
+            &Fees::default(),
+        );
+        let without_top_sport = test_support::arbitrage(vec![c_bet, oly_bet], &Fees::default());
+        let id = all.id.clone();
+        assert_ne!(without_top_sport.id, id);
+        let mut tracker = ArbitrageTracker::new(0.0);
+        let changes = tracker.update(at(0), vec![all]);
+        assert!(changes[0].announce);
+
+        let changes = tracker.update(at(10), vec![without_top_sport]);
+
+        assert_eq!(kinds(&changes), [ChangeKind::Changed]);
+        assert_eq!(changes[0].arbitrage.id, id);
+        assert_eq!(changes[0].lifecycle.first_seen, at(0));
+        assert!(!changes[0].announce);
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    collections::BTreeMap,
+    sync::{PoisonError, RwLock},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::{arbitrage::PossibleArbitrage, lifecycle::ArbitrageChange, models::SportEvent};
+
+/// Changes a subscriber may fall behind on before it starts missing them.
+const CHANGES_CAPACITY: usize = 256;
+
+/// Latest odds and arbitrages of a running watch, shared with whatever serves them.
+pub struct LiveState {
+    snapshot: RwLock<Snapshot>,
+    changes: broadcast::Sender<ArbitrageChange>,
+}
+
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    /// When odds were last matched.
+    pub matched_at: Option<DateTime<Utc>>,
+    /// Events of the latest successful parse of every provider that isn't currently failing.
+    pub events: BTreeMap<String, Vec<SportEvent>>,
+    /// Every event matched across providers, arbitrage or not.
+    pub arbitrages: Vec<PossibleArbitrage>,
+    pub providers: BTreeMap<String, ProviderHealth>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProviderHealth {
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_failure: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    /// Events returned by the latest successful parse.
+    pub events: usize,
+}
+
+impl ProviderHealth {
+    pub fn status(&self) -> &'static str {
+        match (self.last_success, self.last_failure) {
+            (None, None) => "pending",
+            (Some(success), Some(failure)) if success > failure => "ok",
+            (Some(_), None) => "ok",
+            _ => "failing",
+        }
+    }
+}
+
+impl LiveState {
+    pub fn new<'a>(providers: impl IntoIterator<Item = &'a str>) -> Self {
+        let snapshot = Snapshot {
+            providers: providers
+                .into_iter()
+                .map(|provider| (provider.to_string(), ProviderHealth::default()))
+                .collect(),
+            ..Snapshot::default()
+        };
+
+        LiveState {
+            snapshot: RwLock::new(snapshot),
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+        }
+    }
+
+    /// A failing provider's events are dropped, the same way watch stops matching them.
+    pub fn record_parse(
+        &self,
+        provider: &str,
+        scraped_at: DateTime<Utc>,
+        result: Result<&[SportEvent], &str>,
+    ) {
+        let mut snapshot = self
+            .snapshot
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let health = snapshot.providers.entry(provider.to_string()).or_default();
+        match result {
+            Ok(events) => {
+                health.last_success = Some(scraped_at);
+                health.events = events.len();
+                snapshot
+                    .events
+                    .insert(provider.to_string(), events.to_vec());
+            }
+            Err(error) => {
+                health.last_failure = Some(scraped_at);
+                health.last_error = Some(error.to_string());
+                snapshot.events.remove(provider);
+            }
+        }
+    }
+
+    pub fn record_matching(&self, matched_at: DateTime<Utc>, arbitrages: &[PossibleArbitrage]) {
+        let mut snapshot = self
+            .snapshot
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        snapshot.matched_at = Some(matched_at);
+        snapshot.arbitrages = arbitrages.to_vec();
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        self.snapshot
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Passes arbitrages that appeared, changed or disappeared on to every subscriber.
+    pub fn publish(&self, changes: &[ArbitrageChange]) {
+        for change in changes {
+            // Fails only when nobody is subscribed, which is fine
+            let _ = self.changes.send(change.clone());
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ArbitrageChange> {
+        self.changes.subscribe()
+    }
+}
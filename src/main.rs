@@ -1,221 +1,252 @@
-use std::{error::Error, vec};
-
-use models::SportEvent;
-use parser::BookieParser;
-use playwright::Playwright;
-use unicode_normalization::UnicodeNormalization;
-
+use std::{error::Error, sync::Arc, time::Duration};
+
+use backtest::{backtest, Strategy};
+use chrono::{DateTime, Days, Utc};
+use clap::Parser;
+use cli::{Cli, Command, OutputArgs};
+use config::Config;
+use digest::Digest;
+use lifecycle::{track_once, ArbitrageChange, ChangeKind};
+use live_state::LiveState;
+use movements::odds_movements;
+use notifier::Notifications;
+use notifiers::email::EmailNotifier;
+use output::{print_arbitrages, print_backtest, print_movements, print_scan, GridStream};
+use providers::Providers;
+use replay::replay;
+use scan::{scan, ScanResult};
+use stakes::StakePlanner;
+use storage::Storage;
+use watch::watch;
+
+mod api;
+mod arbitrage;
+mod backtest;
+mod cli;
+mod config;
+mod content_source;
+mod content_sources {
+    pub mod browser;
+    pub mod file;
+    pub mod http;
+    mod http_client_extensions;
+    pub mod recording;
+}
+mod digest;
+mod fees;
+mod lifecycle;
+mod live_state;
 mod models;
+mod movements;
+mod notifier;
+mod notifiers {
+    pub mod email;
+    pub mod telegram;
+    pub mod webhook;
+}
+mod output;
+mod outputs {
+    pub mod csv;
+    pub mod json;
+    pub mod table;
+}
 mod parser;
 mod parsers {
-    pub mod bet_safe;
-    pub mod c_bet;
-    mod http_client_extensions;
     pub mod oly_bet;
     pub mod top_sport;
+    pub mod wpt_table;
 }
+mod providers;
+mod replay;
+mod scan;
+mod stakes;
+mod storage;
+#[cfg(test)]
+mod test_support;
+mod watch;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let playwright = Playwright::initialize().await?;
-    playwright.prepare()?; // Install browsers
-    let chromium = playwright.chromium();
-    let browser = chromium.launcher().headless(true).launch().await?;
-    let context = browser.context_builder().build().await?;
-
-    let top_sport_parser = parsers::top_sport::TopSportParser::new();
-
-    let page = context.new_page().await?;
-    let bet_safe_parser = parsers::bet_safe::BetSafePraser::new(page);
-
-    let page = context.new_page().await?;
-    let oly_bet_parser = parsers::oly_bet::OlyBetParser::new(page);
-
-    let page = context.new_page().await?;
-    let c_bet_parser = parsers::c_bet::CBetPraser::new(page);
-
-    let top_sport_events_future = top_sport_parser.parse();
-    let bet_safe_events_future = bet_safe_parser.parse();
-    let oly_bet_events_future = oly_bet_parser.parse();
-    let c_bet_events_future = c_bet_parser.parse();
-
-    let (top_sport_events, bet_safe_events, oly_bet_events, c_bet_events) = tokio::join!(
-        top_sport_events_future,
-        bet_safe_events_future,
-        oly_bet_events_future,
-        c_bet_events_future
-    );
-
-    let events_by_provider = vec![
-        top_sport_events?,
-        bet_safe_events?,
-        oly_bet_events?,
-        c_bet_events?,
-    ];
-
-    println!("{:#?}", find_arbitrages(events_by_provider)?);
-
-    Ok(())
-}
-
-#[derive(Debug)]
-pub struct PossibleArbitrage {
-    pub team1: String,
-    pub team2: String,
-    pub kof1: f64,
-    pub kof_draw: f64,
-    pub kof2: f64,
-    pub kof1_provider: String,
-    pub kof_draw_provider: String,
-    pub kof2_provider: String,
-    pub is_arbitrage: bool,
-    pub providers_offering_bets: Vec<String>,
-}
-
-fn find_arbitrages(
-    events_by_provider: Vec<Vec<SportEvent>>,
-) -> Result<(Vec<PossibleArbitrage>, Vec<SportEvent>), Box<dyn Error>> {
-    let grouped_events = group_events(events_by_provider)?;
+    let cli = Cli::parse();
+    let config = Config::load(&cli.config)?;
+    let stake_planner = config.stake_planner();
+    let mut storage = match &config.storage {
+        Some(storage) => Some(Storage::open(&storage.path)?),
+        None => None,
+    };
+
+    match cli.command {
+        Command::Scan {
+            scrape,
+            output,
+            save,
+        } => {
+            let providers = Providers::build(&config, &scrape).await?;
+            let result = scan(&providers, &config.fees(), storage.as_mut()).await?;
+            if let Some(path) = save {
+                result.save(&path)?;
+            }
 
-    let mut events_wo_pairs = vec![];
-    let mut possible_arbitrages = vec![];
-    for mut event_group in grouped_events {
-        if event_group.len() == 1 {
-            events_wo_pairs.push(event_group.pop().ok_or("")?);
-            continue;
+            print_scan(&result, &output, &stake_planner, config.stakes.total)?;
+            let changes = track_once(
+                storage.as_mut(),
+                result.timestamp,
+                result.arbitrages.clone(),
+                config.notifications.min_profit,
+            )?;
+            Notifications::build(&config)?
+                .announce(result.timestamp, &changes)
+                .await;
         }
-
-        let first_event = &event_group[0];
-        let mut possible_arbitrage = PossibleArbitrage {
-            team1: first_event.team1.clone(),
-            team2: first_event.team2.clone(),
-            kof1: first_event.kof1,
-            kof_draw: first_event.kof_draw,
-            kof2: first_event.kof2,
-            kof1_provider: first_event.provider.clone(),
-            kof_draw_provider: first_event.provider.clone(),
-            kof2_provider: first_event.provider.clone(),
-            is_arbitrage: false,
-            providers_offering_bets: vec![first_event.provider.clone()],
-        };
-
-        for event in event_group.iter().skip(1) {
-            if event.kof1 > possible_arbitrage.kof1 {
-                possible_arbitrage.kof1 = event.kof1;
-                possible_arbitrage.kof1_provider = event.provider.clone();
+        Command::Watch {
+            scrape,
+            output,
+            interval,
+            listen,
+        } => {
+            let providers = Providers::build(&config, &scrape).await?;
+            let intervals = providers
+                .refresh_intervals
+                .iter()
+                .map(|refresh_interval| Duration::from_secs(refresh_interval.unwrap_or(interval)))
+                .collect::<Vec<_>>();
+            let notifications = Arc::new(Notifications::build(&config)?);
+            let state = Arc::new(LiveState::new(
+                providers.parsers.iter().map(|parser| parser.provider()),
+            ));
+            if let Some(address) = listen {
+                api::start(&address, state.clone()).await?;
             }
-            if event.kof2 > possible_arbitrage.kof2 {
-                possible_arbitrage.kof2 = event.kof2;
-                possible_arbitrage.kof2_provider = event.provider.clone();
-            }
-            if event.kof_draw > possible_arbitrage.kof_draw {
-                possible_arbitrage.kof_draw = event.kof_draw;
-                possible_arbitrage.kof_draw_provider = event.provider.clone();
+            let mut grid =
+                GridStream::new(providers.parsers.iter().map(|parser| parser.provider()));
+
+            watch(
+                &providers,
+                &intervals,
+                &config.fees(),
+                &config,
+                storage.as_mut(),
+                &state,
+                |timestamp, changes| {
+                    print_watched(
+                        timestamp,
+                        changes,
+                        &output,
+                        &stake_planner,
+                        config.stakes.total,
+                        &mut grid,
+                    );
+
+                    // Announced in the background, a slow webhook mustn't hold back the next parse
+                    let notifications = notifications.clone();
+                    let changes = changes.to_vec();
+                    tokio::spawn(async move { notifications.announce(timestamp, &changes).await });
+                },
+            )
+            .await;
+        }
+        Command::Report { input, output } => {
+            let result = ScanResult::load(&input)?;
+            print_scan(&result, &output, &stake_planner, config.stakes.total)?;
+        }
+        Command::Replay { recording, output } => {
+            let mut grid =
+                GridStream::new(config.providers.iter().map(|provider| provider.id.as_str()));
+            replay(&config, &recording, &config.fees(), |timestamp, changes| {
+                print_watched(
+                    timestamp,
+                    changes,
+                    &output,
+                    &stake_planner,
+                    config.stakes.total,
+                    &mut grid,
+                )
+            })
+            .await?;
+        }
+        Command::Movements {
+            provider,
+            since,
+            sharp,
+        } => {
+            let storage =
+                storage.ok_or("movements are read from the database, configure [storage]")?;
+            let scrapes = storage.scrapes(provider.as_deref(), since, None)?;
+            let mut movements = odds_movements(&scrapes, config.movements.sharp_move);
+            if sharp {
+                movements.retain(|movement| movement.is_sharp);
             }
 
-            possible_arbitrage
-                .providers_offering_bets
-                .push(event.provider.clone())
+            print_movements(&movements);
         }
-
-        possible_arbitrage.mark_is_arbitrage();
-
-        possible_arbitrages.push(possible_arbitrage);
-    }
-
-    return Ok((possible_arbitrages, events_wo_pairs));
-}
-
-impl PossibleArbitrage {
-    fn mark_is_arbitrage(&mut self) {
-        let bet_amount_1 = 1.0 / (1.0 + self.kof1 / self.kof2 + self.kof1 / self.kof_draw);
-        let bet_amount_draw = 1.0 / (1.0 + self.kof_draw / self.kof1 + self.kof_draw / self.kof2);
-        let bet_amount_2 = 1.0 / (1.0 + self.kof2 / self.kof1 + self.kof2 / self.kof_draw);
-
-        let bet_1_win = bet_amount_1 * self.kof1;
-        let bet_draw_win = bet_amount_draw * self.kof_draw;
-        let bet_2_win = bet_amount_2 * self.kof2;
-
-        self.is_arbitrage = bet_1_win > 1.0 || bet_draw_win > 1.0 || bet_2_win > 1.0;
-    }
-}
-
-fn group_events(
-    mut events_by_provider: Vec<Vec<SportEvent>>,
-) -> Result<Vec<Vec<SportEvent>>, Box<dyn Error>> {
-    let mut grouped_events: Vec<Vec<SportEvent>> = vec![];
-
-    while let Some(mut base_provider_events) = events_by_provider.pop() {
-        while let Some(base_sport_event) = base_provider_events.pop() {
-            let mut matching_events: Vec<SportEvent> = vec![];
-
-            for provider_events in events_by_provider.iter_mut() {
-                let mut found_matching_event: Option<(usize, bool)> = Option::None;
-                for (i, sport_event) in provider_events.iter().enumerate() {
-                    let (events_match, order_matches) =
-                        compare_events(&base_sport_event, sport_event);
-                    if events_match {
-                        found_matching_event = Option::Some((i, order_matches));
-                        break;
-                    }
-                }
-
-                if let Some((matching_event_index, order_matches)) = found_matching_event {
-                    let mut event = provider_events.remove(matching_event_index);
-                    if !order_matches {
-                        event.switch_teams();
-                    }
-
-                    matching_events.push(event);
-                }
+        Command::Digest { date, print } => {
+            let storage =
+                storage.ok_or("digests are read from the database, configure [storage]")?;
+            let yesterday = Utc::now()
+                .date_naive()
+                .checked_sub_days(Days::new(1))
+                .ok_or("invalid date")?;
+            let digest = Digest::load(&storage, date.unwrap_or(yesterday))?;
+
+            if print {
+                print!("{}", digest.to_text());
+            } else {
+                let email =
+                    config.notifications.email.as_ref().ok_or(
+                        "configure [notifications.email] to send digests, or --print them",
+                    )?;
+                EmailNotifier::new(email)?
+                    .send(&digest.subject(), digest.to_text())
+                    .await?;
             }
-
-            matching_events.push(base_sport_event);
-
-            grouped_events.push(matching_events);
+        }
+        Command::Backtest {
+            from,
+            to,
+            min_profit,
+            stake,
+            latency,
+        } => {
+            let storage = storage.ok_or("backtests run over the database, configure [storage]")?;
+            let scrapes = storage.scrapes(None, from, to)?;
+            let strategy = Strategy {
+                min_profit,
+                stake: stake.unwrap_or(config.stakes.total),
+                latency: chrono::Duration::seconds(latency as i64),
+            };
+
+            let report = backtest(&scrapes, &config.fees(), &stake_planner, &strategy)?;
+            print_backtest(&report);
         }
     }
 
-    Ok(grouped_events)
+    Ok(())
 }
 
-fn compare_events(event_1: &SportEvent, event_2: &SportEvent) -> (bool, bool) {
-    let event_1_team_1 = sanitize_team_name(event_1.team1.clone());
-    let event_1_team_2 = sanitize_team_name(event_1.team2.clone());
-    let event_2_team_1 = sanitize_team_name(event_2.team1.clone());
-    let event_2_team_2 = sanitize_team_name(event_2.team2.clone());
-
-    let team_1_1_names_match = event_1_team_1 == event_2_team_1
-        || event_1_team_1.contains(&event_2_team_1)
-        || event_2_team_1.contains(&event_1_team_1);
-
-    let team_2_2_names_match = event_1_team_2 == event_2_team_2
-        || event_1_team_2.contains(&event_2_team_2)
-        || event_2_team_2.contains(&event_1_team_2);
-
-    if team_1_1_names_match && team_2_2_names_match {
-        return (true, true);
+/// Prints the arbitrages that appeared or changed, the disappeared ones are only streamed.
+fn print_watched(
+    timestamp: DateTime<Utc>,
+    changes: &[ArbitrageChange],
+    output: &OutputArgs,
+    stake_planner: &StakePlanner,
+    total_stake: f64,
+    grid: &mut GridStream,
+) {
+    let arbitrages = changes
+        .iter()
+        .filter(|change| change.change != ChangeKind::Disappeared)
+        .map(|change| change.arbitrage.clone())
+        .collect::<Vec<_>>();
+
+    // Keeps going, the next update may well be printable
+    if let Err(e) = print_arbitrages(
+        timestamp,
+        &arbitrages,
+        output,
+        stake_planner,
+        total_stake,
+        grid,
+    ) {
+        eprintln!("couldn't print arbitrages: {}", e);
     }
-
-    let team_1_2_names_match = event_1_team_1 == event_2_team_2
-        || event_1_team_1.contains(&event_2_team_2)
-        || event_2_team_2.contains(&event_1_team_1);
-
-    let team_2_1_names_match = event_1_team_2 == event_2_team_1
-        || event_1_team_2.contains(&event_2_team_1)
-        || event_2_team_1.contains(&event_1_team_2);
-
-    if team_1_2_names_match && team_2_1_names_match {
-        return (true, false);
-    }
-
-    (false, false)
-}
-
-fn sanitize_team_name(team: String) -> String {
-    let lowercase = team.to_lowercase();
-    let normalized = lowercase.nfc().collect::<String>();
-    let fc_removed = normalized.replace("fc", "").trim().to_string();
-    let fk_removed = fc_removed.replace("fk", "").trim().to_string();
-    return fk_removed;
 }
@@ -1,4 +1,6 @@
-#[derive(Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SportEvent {
     pub team1: String,
     pub team2: String,
@@ -6,16 +8,18 @@ pub struct SportEvent {
     pub kof2: f64,
     pub kof_draw: f64,
     pub provider: String,
+    /// Page the odds were scraped from.
+    pub source_url: String,
 }
 
 impl SportEvent {
-    pub fn switch_teams(&mut self) -> () {
+    pub fn switch_teams(&mut self) {
         let team1 = self.team1.clone();
-        let kof1 = self.kof1.clone();
+        let kof1 = self.kof1;
 
         self.team1 = self.team2.clone();
         self.team2 = team1;
-        self.kof1 = self.kof2.clone();
+        self.kof1 = self.kof2;
         self.kof2 = kof1;
     }
 }
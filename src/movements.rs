@@ -0,0 +1,193 @@
+use std::{collections::HashMap, fmt};
+
+use chrono::{DateTime, Utc};
+
+use crate::{models::SportEvent, storage::Scrape};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Outcome {
+    Team1,
+    Draw,
+    Team2,
+}
+
+/// Change of a single price between two consecutive scrapes of the same provider.
+#[derive(Debug, Clone)]
+pub struct OddsMovement {
+    pub provider: String,
+    pub team1: String,
+    pub team2: String,
+    pub outcome: Outcome,
+    pub previous_kof: f64,
+    pub kof: f64,
+    pub previous_scraped_at: DateTime<Utc>,
+    pub scraped_at: DateTime<Utc>,
+    /// Moved by at least the configured percentage.
+    pub is_sharp: bool,
+}
+
+impl OddsMovement {
+    pub fn delta(&self) -> f64 {
+        self.kof - self.previous_kof
+    }
+
+    /// Change relative to the previous price, in percent.
+    pub fn change(&self) -> f64 {
+        self.delta() / self.previous_kof * 100.0
+    }
+
+    pub fn direction(&self) -> &'static str {
+        if self.delta() > 0.0 {
+            "up"
+        } else {
+            "down"
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Team1 => write!(f, "1"),
+            Outcome::Draw => write!(f, "X"),
+            Outcome::Team2 => write!(f, "2"),
+        }
+    }
+}
+
+impl fmt::Display for OddsMovement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} - {} {}: {} {} -> {} ({:+.2}, {:+.2}% since {})",
+            self.scraped_at.format("%Y-%m-%d %H:%M:%S"),
+            self.provider,
+            self.team1,
+            self.team2,
+            self.outcome,
+            self.direction(),
+            self.previous_kof,
+            self.kof,
+            self.delta(),
+            self.change(),
+            self.previous_scraped_at.format("%H:%M:%S")
+        )?;
+        if self.is_sharp {
+            write!(f, " sharp")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Prices of `current` that differ from the same provider's `previous` scrape. Events are matched
+/// by their exact team names as a bookie doesn't rename teams between its own scrapes.
+pub fn diff_events(
+    previous: &[SportEvent],
+    previous_scraped_at: DateTime<Utc>,
+    current: &[SportEvent],
+    scraped_at: DateTime<Utc>,
+    sharp_move: f64,
+) -> Vec<OddsMovement> {
+    let mut movements = vec![];
+    for event in current {
+        let Some(previous_event) = previous.iter().find(|previous_event| {
+            previous_event.team1 == event.team1 && previous_event.team2 == event.team2
+        }) else {
+            continue;
+        };
+
+        let prices = [
+            (Outcome::Team1, previous_event.kof1, event.kof1),
+            (Outcome::Draw, previous_event.kof_draw, event.kof_draw),
+            (Outcome::Team2, previous_event.kof2, event.kof2),
+        ];
+        for (outcome, previous_kof, kof) in prices {
+            // Parsers skip events without prices, guard anyway as a change from zero means nothing
+            if previous_kof == kof || previous_kof <= 0.0 || kof <= 0.0 {
+                continue;
+            }
+
+            let mut movement = OddsMovement {
+                provider: event.provider.clone(),
+                team1: event.team1.clone(),
+                team2: event.team2.clone(),
+                outcome,
+                previous_kof,
+                kof,
+                previous_scraped_at,
+                scraped_at,
+                is_sharp: false,
+            };
+            movement.is_sharp = movement.change().abs() >= sharp_move;
+            movements.push(movement);
+        }
+    }
+
+    movements
+}
+
+/// Movements between every pair of consecutive successful scrapes of each provider, `scrapes`
+/// ordered by time. Like in `watch`, a failure in between doesn't reset the prices.
+pub fn odds_movements(scrapes: &[Scrape], sharp_move: f64) -> Vec<OddsMovement> {
+    let mut previous_scrapes: HashMap<&str, &Scrape> = HashMap::new();
+    let mut movements = vec![];
+    for scrape in scrapes.iter().filter(|scrape| scrape.error.is_none()) {
+        if let Some(previous) = previous_scrapes.insert(&scrape.provider, scrape) {
+            movements.extend(diff_events(
+                &previous.events,
+                previous.scraped_at,
+                &scrape.events,
+                scrape.scraped_at,
+                sharp_move,
+            ));
+        }
+    }
+
+    movements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{assert_close, at, event, scrape};
+
+    #[test]
+    fn reports_changed_prices_with_direction_and_sharpness() {
+        let previous = [event("topSport", 2.0, 3.0, 4.0)];
+        let current = [event("topSport", 2.2, 3.0, 3.9)];
+
+        let movements = diff_events(&previous, at(0), &current, at(10), 5.0);
+
+        assert_eq!(movements.len(), 2);
+        let (team1, team2) = (&movements[0], &movements[1]);
+        assert_eq!(team1.outcome, Outcome::Team1);
+        assert_eq!(team1.direction(), "up");
+        assert_close(team1.delta(), 0.2);
+        assert_close(team1.change(), 10.0);
+        assert!(team1.is_sharp);
+        assert_eq!(team2.outcome, Outcome::Team2);
+        assert_eq!(team2.direction(), "down");
+        assert_close(team2.delta(), -0.1);
+        assert_close(team2.change(), -2.5);
+        assert!(!team2.is_sharp);
+    }
+
+    #[test]
+    fn compares_with_last_successful_scrape() {
+        let scrapes = [
+            scrape("topSport", 0, Some([2.0, 3.0, 4.0])),
+            scrape("topSport", 10, None),
+            scrape("betSafe", 15, Some([1.8, 4.0, 4.5])),
+            scrape("topSport", 20, Some([2.2, 3.0, 4.0])),
+        ];
+
+        let movements = odds_movements(&scrapes, 5.0);
+
+        assert_eq!(movements.len(), 1);
+        assert_eq!(movements[0].provider, "topSport");
+        assert_eq!(movements[0].previous_kof, 2.0);
+        assert_eq!(movements[0].previous_scraped_at, at(0));
+        assert_eq!(movements[0].scraped_at, at(20));
+    }
+}
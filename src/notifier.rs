@@ -0,0 +1,125 @@
+use std::{error::Error, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::header::CONTENT_TYPE;
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    config::Config,
+    lifecycle::ArbitrageChange,
+    notifiers::{telegram::TelegramNotifier, webhook::WebhookNotifier},
+};
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Where notifications go, used when reporting failures.
+    fn name(&self) -> &str;
+    async fn notify(
+        &self,
+        timestamp: DateTime<Utc>,
+        arbitrages: &[&PossibleArbitrage],
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+/// Announces every arbitrage once per lifecycle to each configured notifier.
+pub struct Notifications {
+    notifiers: Vec<Box<dyn Notifier>>,
+}
+
+impl Notifications {
+    pub fn build(config: &Config) -> Result<Notifications, Box<dyn Error>> {
+        let notifications = &config.notifications;
+        let mut notifiers: Vec<Box<dyn Notifier>> = vec![];
+        for webhook in &notifications.webhooks {
+            notifiers.push(Box::new(WebhookNotifier::new(
+                webhook,
+                notifications.retries,
+            )?));
+        }
+        if let Some(telegram) = &notifications.telegram {
+            notifiers.push(Box::new(TelegramNotifier::new(
+                telegram,
+                notifications.retries,
+                config.stake_planner(),
+                config.stakes.total,
+            )?));
+        }
+
+        Ok(Notifications { notifiers })
+    }
+
+    /// Sends arbitrages whose return reached the configured `min_profit` for the first time in
+    /// their lifecycle, the tracker flags those. They aren't announced again while they stay around,
+    /// and with `[storage]` not even by later runs. Without it every `scan` starts afresh and
+    /// announces them again. Failures are reported on stderr without stopping the other notifiers.
+    pub async fn announce(&self, timestamp: DateTime<Utc>, changes: &[ArbitrageChange]) {
+        let new_arbitrages = changes
+            .iter()
+            .filter(|change| change.announce)
+            .map(|change| &change.arbitrage)
+            .collect::<Vec<_>>();
+        if new_arbitrages.is_empty() {
+            return;
+        }
+
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify(timestamp, &new_arbitrages).await {
+                eprintln!("couldn't notify {}: {}", notifier.name(), e);
+            }
+        }
+    }
+}
+
+/// One line summary of an arbitrage for chat messages.
+pub fn describe(arbitrage: &PossibleArbitrage) -> String {
+    format!(
+        "{} - {}: {:.2}% guaranteed return, 1 @ {} on {}, X @ {} on {}, 2 @ {} on {}",
+        arbitrage.team1,
+        arbitrage.team2,
+        arbitrage.guaranteed_return,
+        arbitrage.kof1,
+        arbitrage.kof1_provider,
+        arbitrage.kof_draw,
+        arbitrage.kof_draw_provider,
+        arbitrage.kof2,
+        arbitrage.kof2_provider
+    )
+}
+
+/// POSTs `payload` as JSON, retrying failures `retries` times while backing off 1, 2, 4... seconds
+/// so a briefly unavailable service gets a chance to recover.
+pub async fn post_json(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &str,
+    retries: u32,
+) -> Result<(), Box<dyn Error>> {
+    let mut attempt = 0;
+    loop {
+        let error = match post(client, url, payload).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        if attempt == retries {
+            return Err(format!("gave up after {} attempts: {}", attempt + 1, error).into());
+        }
+
+        tokio::time::sleep(Duration::from_secs(1 << attempt.min(6))).await;
+        attempt += 1;
+    }
+}
+
+/// Errors leave out the url, it may hold a bot token or be a secret webhook itself.
+async fn post(client: &reqwest::Client, url: &str, payload: &str) -> Result<(), reqwest::Error> {
+    client
+        .post(url)
+        .header(CONTENT_TYPE, "application/json")
+        .body(payload.to_string())
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| e.without_url())?;
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use std::error::Error;
+
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::config::{EmailConfig, SmtpSecurity};
+
+/// Sends plain text emails through an SMTP server.
+pub struct EmailNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailNotifier {
+    pub fn new(config: &EmailConfig) -> Result<Self, Box<dyn Error>> {
+        let mut builder = match config.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?
+            }
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+            }
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        let to = config
+            .to
+            .iter()
+            .map(|to| to.parse())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid recipient: {}", e))?;
+        if to.is_empty() {
+            return Err("emails need at least one recipient in `to`".into());
+        }
+
+        Ok(EmailNotifier {
+            transport: builder.build(),
+            from: config
+                .from
+                .parse()
+                .map_err(|e| format!("invalid sender {}: {}", config.from, e))?,
+            to,
+        })
+    }
+
+    pub async fn send(&self, subject: &str, body: String) -> Result<(), Box<dyn Error>> {
+        let mut message = Message::builder().from(self.from.clone());
+        for to in &self.to {
+            message = message.to(to.clone());
+        }
+        let message = message
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)?;
+
+        self.transport.send(message).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    config::TelegramConfig,
+    notifier::{post_json, Notifier},
+    stakes::StakePlanner,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest text the Bot API accepts in a single message.
+const MAX_MESSAGE_LENGTH: usize = 4096;
+
+/// Sends arbitrages through the Telegram Bot API rendered with the configured template, in messages
+/// spaced at least `min_interval` apart. Updates arriving before the next message may go out are
+/// collapsed into it.
+pub struct TelegramNotifier {
+    client: reqwest::Client,
+    url: String,
+    chat_id: String,
+    template: String,
+    max_arbitrages: usize,
+    min_interval: Duration,
+    retries: u32,
+    stake_planner: StakePlanner,
+    total_stake: f64,
+    slot: Mutex<Slot>,
+}
+
+struct Slot {
+    next_message_at: Instant,
+    /// Arbitrages of the message waiting for `next_message_at`, if one is.
+    pending: Option<Vec<PossibleArbitrage>>,
+}
+
+impl TelegramNotifier {
+    pub fn new(
+        config: &TelegramConfig,
+        retries: u32,
+        stake_planner: StakePlanner,
+        total_stake: f64,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(TelegramNotifier {
+            client: reqwest::Client::builder().timeout(TIMEOUT).build()?,
+            url: format!(
+                "{}/bot{}/sendMessage",
+                config.api_url.trim_end_matches('/'),
+                config.token
+            ),
+            chat_id: config.chat_id.clone(),
+            template: config.template.clone(),
+            max_arbitrages: config.max_arbitrages,
+            min_interval: Duration::from_secs(config.min_interval),
+            retries,
+            stake_planner,
+            total_stake,
+            slot: Mutex::new(Slot {
+                next_message_at: Instant::now(),
+                pending: None,
+            }),
+        })
+    }
+
+    /// The most profitable arbitrages rendered one after another, the rest only counted.
+    fn message(&self, arbitrages: &[&PossibleArbitrage]) -> String {
+        let mut arbitrages = arbitrages.to_vec();
+        arbitrages.sort_by(|a, b| {
+            b.guaranteed_return
+                .partial_cmp(&a.guaranteed_return)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        let mut parts = arbitrages
+            .iter()
+            .take(self.max_arbitrages)
+            .map(|arbitrage| self.render(arbitrage))
+            .collect::<Vec<_>>();
+        if arbitrages.len() > self.max_arbitrages {
+            parts.push(format!(
+                "...and {} more arbitrages",
+                arbitrages.len() - self.max_arbitrages
+            ));
+        }
+
+        parts
+            .join("\n\n")
+            .chars()
+            .take(MAX_MESSAGE_LENGTH)
+            .collect()
+    }
+
+    fn render(&self, arbitrage: &PossibleArbitrage) -> String {
+        // Stakes a provider's limits don't allow still leave the odds worth knowing about
+        let plan = self.stake_planner.plan(arbitrage, self.total_stake).ok();
+        let amount = |amount: Option<f64>| match amount {
+            Some(amount) => format!("{:.2}", amount),
+            None => "n/a".to_string(),
+        };
+
+        let values = [
+            ("{team1}", arbitrage.team1.clone()),
+            ("{team2}", arbitrage.team2.clone()),
+            ("{return}", format!("{:.2}", arbitrage.guaranteed_return)),
+            ("{kof1}", arbitrage.kof1.to_string()),
+            ("{kof1_provider}", arbitrage.kof1_provider.clone()),
+            ("{kof_draw}", arbitrage.kof_draw.to_string()),
+            ("{kof_draw_provider}", arbitrage.kof_draw_provider.clone()),
+            ("{kof2}", arbitrage.kof2.to_string()),
+            ("{kof2_provider}", arbitrage.kof2_provider.clone()),
+            (
+                "{stake1}",
+                amount(plan.as_ref().map(|plan| plan.kof1.stake)),
+            ),
+            (
+                "{stake_draw}",
+                amount(plan.as_ref().map(|plan| plan.kof_draw.stake)),
+            ),
+            (
+                "{stake2}",
+                amount(plan.as_ref().map(|plan| plan.kof2.stake)),
+            ),
+            (
+                "{total_stake}",
+                amount(plan.as_ref().map(|plan| plan.total_cost)),
+            ),
+            (
+                "{profit}",
+                amount(plan.as_ref().map(|plan| plan.guaranteed_profit)),
+            ),
+        ];
+
+        values
+            .iter()
+            .fold(self.template.clone(), |message, (placeholder, value)| {
+                message.replace(placeholder, value)
+            })
+    }
+
+    /// Takes the next free slot for `arbitrages`. While a message is already waiting for it they
+    /// are merged into that one instead, newer prices replacing older ones, and `None` is returned.
+    fn reserve_slot(
+        &self,
+        arbitrages: &[&PossibleArbitrage],
+    ) -> Result<Option<Duration>, Box<dyn Error>> {
+        let mut slot = self.slot.lock().map_err(|e| e.to_string())?;
+        if let Some(pending) = &mut slot.pending {
+            for arbitrage in arbitrages {
+                pending.retain(|pending| pending.id != arbitrage.id);
+                pending.push((*arbitrage).clone());
+            }
+            return Ok(None);
+        }
+
+        let now = Instant::now();
+        let wait = slot.next_message_at.saturating_duration_since(now);
+        if wait.is_zero() {
+            slot.next_message_at = now + self.min_interval;
+        } else {
+            slot.pending = Some(
+                arbitrages
+                    .iter()
+                    .map(|arbitrage| (*arbitrage).clone())
+                    .collect(),
+            );
+        }
+
+        Ok(Some(wait))
+    }
+
+    /// Hands out whatever collected for the message waiting for the slot, which starts now.
+    fn take_pending(&self) -> Result<Vec<PossibleArbitrage>, Box<dyn Error>> {
+        let mut slot = self.slot.lock().map_err(|e| e.to_string())?;
+        slot.next_message_at = Instant::now() + self.min_interval;
+
+        Ok(slot.pending.take().unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        "telegram"
+    }
+
+    async fn notify(
+        &self,
+        _timestamp: DateTime<Utc>,
+        arbitrages: &[&PossibleArbitrage],
+    ) -> Result<(), Box<dyn Error>> {
+        let wait = self.reserve_slot(arbitrages)?;
+        let text = match wait {
+            None => return Ok(()),
+            Some(wait) if wait.is_zero() => self.message(arbitrages),
+            Some(wait) => {
+                tokio::time::sleep(wait).await;
+                let pending = self.take_pending()?;
+                self.message(&pending.iter().collect::<Vec<_>>())
+            }
+        };
+
+        let payload = json!({ "chat_id": self.chat_id, "text": text });
+        post_json(&self.client, &self.url, &payload.to_string(), self.retries).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fees::Fees,
+        stakes::StakeRounding,
+        test_support::{self, event, named_event},
+    };
+
+    /// Notifier with the default settings, overridden by the `config` lines.
+    fn notifier(config: &str) -> TelegramNotifier {
+        let config: TelegramConfig =
+            toml::from_str(&format!("token = \"TOKEN\"\nchat_id = \"42\"\n{}", config)).unwrap();
+        let stake_planner = StakePlanner::new(StakeRounding::new(1.0), Fees::default());
+
+        TelegramNotifier::new(&config, 0, stake_planner, 100.0).unwrap()
+    }
+
+    /// Žalgiris - Galatasaray with Galatasaray's win at `bet_safe_kof2`, a 7.9% return at 4.5.
+    fn arbitrage(bet_safe_kof2: f64) -> PossibleArbitrage {
+        test_support::arbitrage(
+            vec![
+                event("topSport", 2.2, 3.0, 3.0),
+                event("betSafe", 1.8, 4.0, bet_safe_kof2),
+            ],
+            &Fees::default(),
+        )
+    }
+
+    /// Ferencvaros - KÍ Klaksvík, a 3.3% return.
+    fn other_arbitrage() -> PossibleArbitrage {
+        test_support::arbitrage(
+            vec![
+                named_event("topSport", "Ferencvaros", "KÍ Klaksvík", [1.6, 4.0, 6.0]),
+                named_event("betSafe", "Ferencvaros", "KÍ Klaksvík", [1.5, 5.0, 7.0]),
+            ],
+            &Fees::default(),
+        )
+    }
+
+    #[test]
+    fn fills_every_placeholder() {
+        let message = notifier("").render(&arbitrage(4.5));
+
+        assert_eq!(
+            message,
+            "Žalgiris - Galatasaray: 7.90% guaranteed return
+1 @ 2.2 on topSport, stake 49.00
+X @ 4 on betSafe, stake 27.00
+2 @ 4.5 on betSafe, stake 24.00
+staked 100.00, profit 7.80"
+        );
+    }
+
+    #[test]
+    fn counts_arbitrages_beyond_max() {
+        let notifier = notifier("template = \"{team1} - {team2}\"\nmax_arbitrages = 1");
+
+        let message = notifier.message(&[&other_arbitrage(), &arbitrage(4.5)]);
+
+        assert_eq!(
+            message,
+            "Žalgiris - Galatasaray\n\n...and 1 more arbitrages"
+        );
+    }
+
+    #[test]
+    fn merges_updates_into_pending_message() {
+        let notifier = notifier("min_interval = 30");
+        let (first, older, newer, other) = (
+            arbitrage(4.5),
+            arbitrage(5.0),
+            arbitrage(6.0),
+            other_arbitrage(),
+        );
+        assert_eq!(older.id, newer.id);
+
+        assert_eq!(
+            notifier.reserve_slot(&[&first]).unwrap(),
+            Some(Duration::ZERO)
+        );
+        let wait = notifier.reserve_slot(&[&older]).unwrap();
+        assert!(wait.is_some_and(|wait| !wait.is_zero()));
+        assert_eq!(notifier.reserve_slot(&[&newer, &other]).unwrap(), None);
+
+        let pending = notifier.take_pending().unwrap();
+        let kofs = pending
+            .iter()
+            .map(|arbitrage| arbitrage.kof2)
+            .collect::<Vec<_>>();
+        assert_eq!(kofs, [6.0, other.kof2]);
+        assert!(notifier.take_pending().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+use std::{error::Error, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    config::{WebhookConfig, WebhookFormat},
+    notifier::{describe, post_json, Notifier},
+    outputs::json::arbitrages_to_json,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest `content` Discord accepts, it rejects the whole message otherwise.
+const MAX_DISCORD_CONTENT_LENGTH: usize = 2000;
+
+/// POSTs arbitrages to a chat webhook, or as the `--format json` document to anything else.
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    /// Format and host only, the url itself is a secret.
+    name: String,
+    url: String,
+    format: WebhookFormat,
+    retries: u32,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: &WebhookConfig, retries: u32) -> Result<Self, Box<dyn Error>> {
+        let host = reqwest::Url::parse(&config.url)?
+            .host_str()
+            .unwrap_or_default()
+            .to_string();
+        let format = match config.format {
+            WebhookFormat::Slack => "slack",
+            WebhookFormat::Discord => "discord",
+            WebhookFormat::Generic => "generic",
+        };
+
+        Ok(WebhookNotifier {
+            client: reqwest::Client::builder().timeout(TIMEOUT).build()?,
+            name: format!("{} webhook on {}", format, host),
+            url: config.url.clone(),
+            format: config.format,
+            retries,
+        })
+    }
+
+    fn payload(
+        &self,
+        timestamp: DateTime<Utc>,
+        arbitrages: &[&PossibleArbitrage],
+    ) -> Result<String, Box<dyn Error>> {
+        let message = || {
+            arbitrages
+                .iter()
+                .map(|arbitrage| describe(arbitrage))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        Ok(match self.format {
+            WebhookFormat::Slack => json!({ "text": message() }).to_string(),
+            WebhookFormat::Discord => {
+                let content = message()
+                    .chars()
+                    .take(MAX_DISCORD_CONTENT_LENGTH)
+                    .collect::<String>();
+                json!({ "content": content }).to_string()
+            }
+            WebhookFormat::Generic => arbitrages_to_json(timestamp, arbitrages)?,
+        })
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn notify(
+        &self,
+        timestamp: DateTime<Utc>,
+        arbitrages: &[&PossibleArbitrage],
+    ) -> Result<(), Box<dyn Error>> {
+        let payload = self.payload(timestamp, arbitrages)?;
+        post_json(&self.client, &self.url, &payload, self.retries).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        fees::Fees,
+        test_support::{self, at, event},
+    };
+
+    fn notifier(format: &str) -> WebhookNotifier {
+        let config: WebhookConfig = toml::from_str(&format!(
+            "url = \"https://hooks.example.com/services/SECRET\"\nformat = \"{}\"",
+            format
+        ))
+        .unwrap();
+
+        WebhookNotifier::new(&config, 0).unwrap()
+    }
+
+    fn arbitrage() -> PossibleArbitrage {
+        test_support::arbitrage(
+            vec![
+                event("topSport", 2.2, 3.0, 3.0),
+                event("betSafe", 1.8, 4.0, 4.5),
+            ],
+            &Fees::default(),
+        )
+    }
+
+    fn payload(format: &str, arbitrages: &[&PossibleArbitrage]) -> Value {
+        let payload = notifier(format).payload(at(0), arbitrages).unwrap();
+        serde_json::from_str(&payload).unwrap()
+    }
+
+    #[test]
+    fn names_webhook_without_its_url() {
+        assert_eq!(
+            notifier("slack").name(),
+            "slack webhook on hooks.example.com"
+        );
+    }
+
+    #[test]
+    fn sends_slack_text() {
+        let arbitrage = arbitrage();
+
+        let payload = payload("slack", &[&arbitrage, &arbitrage]);
+
+        let line = describe(&arbitrage);
+        assert_eq!(payload, json!({ "text": format!("{}\n{}", line, line) }));
+    }
+
+    #[test]
+    fn truncates_discord_content_to_limit() {
+        let arbitrage = arbitrage();
+
+        let payload = payload("discord", &[&arbitrage; 30]);
+
+        let content = payload["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), MAX_DISCORD_CONTENT_LENGTH);
+        assert!(content.starts_with(&describe(&arbitrage)));
+        assert_eq!(payload.as_object().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn sends_generic_json_document() {
+        let arbitrage = arbitrage();
+
+        let payload = payload("generic", &[&arbitrage]);
+
+        assert_eq!(payload["timestamp"], json!(at(0)));
+        assert_eq!(payload["arbitrages"][0]["team1"], "Žalgiris");
+        assert_eq!(payload["arbitrages"][0]["kof2_provider"], "betSafe");
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    io::{stdout, IsTerminal},
+};
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    backtest::BacktestReport,
+    cli::{OutputArgs, OutputFormat},
+    movements::OddsMovement,
+    outputs::{csv, json, table},
+    scan::ScanResult,
+    stakes::{StakePlan, StakePlanner},
+};
+
+pub fn print_scan(
+    result: &ScanResult,
+    output: &OutputArgs,
+    stake_planner: &StakePlanner,
+    total_stake: f64,
+) -> Result<(), Box<dyn Error>> {
+    let arbitrages = reported_arbitrages(&result.arbitrages, output.min_profit);
+
+    match output.format {
+        OutputFormat::Text => print_text(result, &arbitrages, stake_planner, total_stake),
+        OutputFormat::Table => print!(
+            "{}",
+            table::scan_to_table(result, output.min_profit, stdout().is_terminal())
+        ),
+        OutputFormat::Json => println!("{}", json::scan_to_json(result, &arbitrages)?),
+        OutputFormat::Jsonl => {
+            for line in json::scan_to_json_lines(result, &arbitrages)? {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            for failure in &result.failed_providers {
+                eprintln!("{} failed: {}", failure.provider, failure.error);
+            }
+
+            // The grid is meant for comparing odds, so it isn't limited to arbitrages
+            let matched_events = result.arbitrages.iter().collect::<Vec<_>>();
+            print!("{}", grid(&matched_events, output.format)?);
+        }
+        OutputFormat::Debug => println!("{:#?}", result),
+    }
+
+    Ok(())
+}
+
+/// CSV/TSV grid printed across the updates of a watch or replay, keeping one header and the same
+/// provider columns throughout.
+pub struct GridStream {
+    providers: Vec<String>,
+    header_printed: bool,
+}
+
+impl GridStream {
+    pub fn new<'a>(providers: impl IntoIterator<Item = &'a str>) -> Self {
+        GridStream {
+            providers: providers.into_iter().map(String::from).collect(),
+            header_printed: false,
+        }
+    }
+}
+
+/// Prints arbitrages found while watching or replaying, in between full scans there are no other
+/// results to show. Unlike the grid of a scan, which lists every matched event, CSV/TSV output only
+/// adds rows for the arbitrages of each update to `grid`.
+pub fn print_arbitrages(
+    timestamp: DateTime<Utc>,
+    arbitrages: &[PossibleArbitrage],
+    output: &OutputArgs,
+    stake_planner: &StakePlanner,
+    total_stake: f64,
+    grid: &mut GridStream,
+) -> Result<(), Box<dyn Error>> {
+    let arbitrages = reported_arbitrages(arbitrages, output.min_profit);
+    if arbitrages.is_empty() {
+        return Ok(());
+    }
+
+    match output.format {
+        OutputFormat::Text => {
+            println!("{}", timestamp.format("%Y-%m-%d %H:%M:%S"));
+            for arbitrage in arbitrages {
+                print_planned_arbitrage(arbitrage, stake_planner, total_stake);
+            }
+        }
+        OutputFormat::Table => print!(
+            "{}",
+            table::arbitrages_to_table(&arbitrages, output.min_profit, stdout().is_terminal())
+        ),
+        OutputFormat::Json => println!("{}", json::arbitrages_to_json(timestamp, &arbitrages)?),
+        OutputFormat::Jsonl => {
+            for line in json::arbitrages_to_json_lines(timestamp, &arbitrages)? {
+                println!("{}", line);
+            }
+        }
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            let providers = grid
+                .providers
+                .iter()
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            print!(
+                "{}",
+                csv::grid_rows_to_csv(
+                    &arbitrages,
+                    &providers,
+                    delimiter(output.format),
+                    !grid.header_printed
+                )?
+            );
+            grid.header_printed = true;
+        }
+        OutputFormat::Debug => println!("{:#?}", arbitrages),
+    }
+
+    Ok(())
+}
+
+/// Lists movements oldest first, followed by how often each provider moved its prices.
+pub fn print_movements(movements: &[OddsMovement]) {
+    let mut counts: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for movement in movements {
+        println!("{}", movement);
+
+        let (moves, sharp_moves) = counts.entry(&movement.provider).or_default();
+        *moves += 1;
+        if movement.is_sharp {
+            *sharp_moves += 1;
+        }
+    }
+
+    for (provider, (moves, sharp_moves)) in counts {
+        println!("{}: {} moves, {} sharp", provider, moves, sharp_moves);
+    }
+}
+
+pub fn print_backtest(report: &BacktestReport) {
+    for taken in &report.taken {
+        print!(
+            "{} {} - {} ({:.2}% guaranteed return): staked {:.2}, profit {:.2}",
+            taken.taken_at.format("%Y-%m-%d %H:%M:%S"),
+            taken.team1,
+            taken.team2,
+            taken.guaranteed_return,
+            taken.total_cost,
+            taken.guaranteed_profit
+        );
+        match &taken.invalidated_by {
+            Some((outcome, provider)) => println!(", invalidated by {} on {}", outcome, provider),
+            None => println!(),
+        }
+    }
+
+    println!(
+        "{} scrapes, {} arbitrages taken, {} skipped by stake limits, {} invalidated by odds moving",
+        report.scrapes,
+        report.taken.len(),
+        report.skipped,
+        report.invalidated()
+    );
+    println!(
+        "staked {:.2}, expected profit {:.2}, {:.2} of it from arbitrages that held",
+        report.total_cost(),
+        report.expected_profit(),
+        report.valid_profit()
+    );
+}
+
+fn grid(arbitrages: &[&PossibleArbitrage], format: OutputFormat) -> Result<String, Box<dyn Error>> {
+    csv::grid_to_csv(arbitrages, delimiter(format))
+}
+
+fn delimiter(format: OutputFormat) -> u8 {
+    match format {
+        OutputFormat::Tsv => b'\t',
+        _ => b',',
+    }
+}
+
+fn reported_arbitrages(
+    arbitrages: &[PossibleArbitrage],
+    min_profit: f64,
+) -> Vec<&PossibleArbitrage> {
+    arbitrages
+        .iter()
+        .filter(|arbitrage| arbitrage.is_arbitrage && arbitrage.guaranteed_return >= min_profit)
+        .collect()
+}
+
+fn print_text(
+    result: &ScanResult,
+    arbitrages: &[&PossibleArbitrage],
+    stake_planner: &StakePlanner,
+    total_stake: f64,
+) {
+    for failure in &result.failed_providers {
+        println!("{} failed: {}", failure.provider, failure.error);
+    }
+
+    for arbitrage in arbitrages {
+        print_planned_arbitrage(arbitrage, stake_planner, total_stake);
+    }
+
+    println!(
+        "{} arbitrages among {} matched events, {} unmatched events",
+        arbitrages.len(),
+        result.arbitrages.len(),
+        result.unmatched_events.len()
+    );
+}
+
+fn print_planned_arbitrage(
+    arbitrage: &PossibleArbitrage,
+    stake_planner: &StakePlanner,
+    total_stake: f64,
+) {
+    match stake_planner.plan(arbitrage, total_stake) {
+        Ok(plan) => print_stake_plan(arbitrage, &plan),
+        Err(e) => println!("{} - {}: {}", arbitrage.team1, arbitrage.team2, e),
+    }
+}
+
+fn print_stake_plan(arbitrage: &PossibleArbitrage, plan: &StakePlan) {
+    println!(
+        "{} - {} ({:.2}% guaranteed return)",
+        arbitrage.team1, arbitrage.team2, arbitrage.guaranteed_return
+    );
+    for (outcome, leg) in ["1", "X", "2"].iter().zip(plan.legs()) {
+        println!(
+            "  {}: {:.2} on {} @ {}",
+            outcome, leg.stake, leg.provider, leg.kof
+        );
+    }
+    println!(
+        "  staked {:.2} ({:.2} with fees), guaranteed payout {:.2}, profit {:.2}",
+        plan.total_stake, plan.total_cost, plan.guaranteed_payout, plan.guaranteed_profit
+    );
+}
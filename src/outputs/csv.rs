@@ -0,0 +1,90 @@
+use std::{collections::BTreeSet, error::Error};
+
+use crate::arbitrage::PossibleArbitrage;
+
+/// Odds comparison grid with one row per matched event: every provider's 1/X/2 odds side by side,
+/// followed by the best price per outcome and the overround.
+pub fn grid_to_csv(
+    arbitrages: &[&PossibleArbitrage],
+    delimiter: u8,
+) -> Result<String, Box<dyn Error>> {
+    let providers = arbitrages
+        .iter()
+        .flat_map(|arbitrage| arbitrage.events.iter().map(|event| event.provider.as_str()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    grid_rows_to_csv(arbitrages, &providers, delimiter, true)
+}
+
+/// Rows of the grid with odds columns for each of `providers`, whether or not they offer the event,
+/// preceded by the header when `header` is set.
+pub fn grid_rows_to_csv(
+    arbitrages: &[&PossibleArbitrage],
+    providers: &[&str],
+    delimiter: u8,
+    header: bool,
+) -> Result<String, Box<dyn Error>> {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(vec![]);
+
+    let mut columns = vec!["team1".to_string(), "team2".to_string()];
+    for provider in providers {
+        for outcome in ["1", "X", "2"] {
+            columns.push(format!("{} {}", provider, outcome));
+        }
+    }
+    columns.extend(
+        [
+            "best 1",
+            "best 1 provider",
+            "best X",
+            "best X provider",
+            "best 2",
+            "best 2 provider",
+            "overround",
+            "margin %",
+            "guaranteed return %",
+            "is arbitrage",
+        ]
+        .map(String::from),
+    );
+    if header {
+        writer.write_record(&columns)?;
+    }
+
+    for arbitrage in arbitrages {
+        let mut row = vec![arbitrage.team1.clone(), arbitrage.team2.clone()];
+        for provider in providers {
+            match arbitrage
+                .events
+                .iter()
+                .find(|event| event.provider == *provider)
+            {
+                Some(event) => row.extend([
+                    event.kof1.to_string(),
+                    event.kof_draw.to_string(),
+                    event.kof2.to_string(),
+                ]),
+                None => row.extend([String::new(), String::new(), String::new()]),
+            }
+        }
+        row.extend([
+            arbitrage.kof1.to_string(),
+            arbitrage.kof1_provider.clone(),
+            arbitrage.kof_draw.to_string(),
+            arbitrage.kof_draw_provider.clone(),
+            arbitrage.kof2.to_string(),
+            arbitrage.kof2_provider.clone(),
+            format!("{:.4}", arbitrage.overround),
+            format!("{:.2}", arbitrage.margin),
+            format!("{:.2}", arbitrage.guaranteed_return),
+            arbitrage.is_arbitrage.to_string(),
+        ]);
+        writer.write_record(&row)?;
+    }
+
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
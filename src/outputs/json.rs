@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    models::SportEvent,
+    scan::{ProviderFailure, ScanResult},
+};
+
+#[derive(Serialize)]
+struct JsonScan<'a> {
+    timestamp: DateTime<Utc>,
+    arbitrages: &'a [&'a PossibleArbitrage],
+    unmatched_events: &'a [SportEvent],
+    provider_counts: &'a BTreeMap<String, usize>,
+    failed_providers: &'a [ProviderFailure],
+}
+
+#[derive(Serialize)]
+struct JsonArbitrages<'a> {
+    timestamp: DateTime<Utc>,
+    arbitrages: &'a [&'a PossibleArbitrage],
+}
+
+/// A single JSON-lines record, tagged with its kind so that consumers can filter lines by `type`.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum JsonLine<'a> {
+    Run {
+        timestamp: DateTime<Utc>,
+        provider_counts: &'a BTreeMap<String, usize>,
+        failed_providers: &'a [ProviderFailure],
+    },
+    Arbitrage {
+        timestamp: DateTime<Utc>,
+        #[serde(flatten)]
+        arbitrage: &'a PossibleArbitrage,
+    },
+    UnmatchedEvent {
+        timestamp: DateTime<Utc>,
+        #[serde(flatten)]
+        event: &'a SportEvent,
+    },
+}
+
+pub fn scan_to_json(
+    result: &ScanResult,
+    arbitrages: &[&PossibleArbitrage],
+) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&JsonScan {
+        timestamp: result.timestamp,
+        arbitrages,
+        unmatched_events: &result.unmatched_events,
+        provider_counts: &result.provider_counts,
+        failed_providers: &result.failed_providers,
+    })
+}
+
+pub fn scan_to_json_lines(
+    result: &ScanResult,
+    arbitrages: &[&PossibleArbitrage],
+) -> Result<Vec<String>, serde_json::Error> {
+    let timestamp = result.timestamp;
+
+    let mut lines = vec![serde_json::to_string(&JsonLine::Run {
+        timestamp,
+        provider_counts: &result.provider_counts,
+        failed_providers: &result.failed_providers,
+    })?];
+    lines.extend(arbitrages_to_json_lines(timestamp, arbitrages)?);
+    for event in &result.unmatched_events {
+        lines.push(serde_json::to_string(&JsonLine::UnmatchedEvent {
+            timestamp,
+            event,
+        })?);
+    }
+
+    Ok(lines)
+}
+
+/// Arbitrages as a single line JSON document, so that every update of a stream stays one line.
+pub fn arbitrages_to_json(
+    timestamp: DateTime<Utc>,
+    arbitrages: &[&PossibleArbitrage],
+) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&JsonArbitrages {
+        timestamp,
+        arbitrages,
+    })
+}
+
+pub fn arbitrages_to_json_lines(
+    timestamp: DateTime<Utc>,
+    arbitrages: &[&PossibleArbitrage],
+) -> Result<Vec<String>, serde_json::Error> {
+    arbitrages
+        .iter()
+        .map(|arbitrage| {
+            serde_json::to_string(&JsonLine::Arbitrage {
+                timestamp,
+                arbitrage,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use crate::{arbitrage::PossibleArbitrage, scan::ScanResult};
+
+const HIGHLIGHT: &str = "\x1b[1;32m";
+const RESET: &str = "\x1b[0m";
+
+/// Matched fixtures sorted by profitability with arbitrages highlighted, followed by a summary of
+/// what every provider returned.
+pub fn scan_to_table(result: &ScanResult, min_profit: f64, color: bool) -> String {
+    let arbitrages = result.arbitrages.iter().collect::<Vec<_>>();
+    let mut table = arbitrages_to_table(&arbitrages, min_profit, color);
+
+    let mut unmatched_counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for event in &result.unmatched_events {
+        *unmatched_counts.entry(&event.provider).or_default() += 1;
+    }
+
+    let arbitrage_count = arbitrages
+        .iter()
+        .filter(|arbitrage| is_highlighted(arbitrage, min_profit))
+        .count();
+
+    table.push('\n');
+    table.push_str(&format!(
+        "{} matched fixtures, {} arbitrages, {} unmatched events\n",
+        arbitrages.len(),
+        arbitrage_count,
+        result.unmatched_events.len()
+    ));
+    for (provider, count) in &result.provider_counts {
+        table.push_str(&format!(
+            "  {}: {} events, {} unmatched\n",
+            provider,
+            count,
+            unmatched_counts.get(provider.as_str()).unwrap_or(&0)
+        ));
+    }
+    for failure in &result.failed_providers {
+        table.push_str(&format!(
+            "  {}: failed, {}\n",
+            failure.provider, failure.error
+        ));
+    }
+
+    table
+}
+
+pub fn arbitrages_to_table(
+    arbitrages: &[&PossibleArbitrage],
+    min_profit: f64,
+    color: bool,
+) -> String {
+    let mut sorted = arbitrages.to_vec();
+    sorted.sort_by(|a, b| b.guaranteed_return.total_cmp(&a.guaranteed_return));
+
+    let header = ["Fixture", "1", "X", "2", "Margin %", "Return %"].map(String::from);
+    let rows = sorted
+        .iter()
+        .map(|arbitrage| {
+            [
+                format!("{} - {}", arbitrage.team1, arbitrage.team2),
+                format!("{} {}", arbitrage.kof1, arbitrage.kof1_provider),
+                format!("{} {}", arbitrage.kof_draw, arbitrage.kof_draw_provider),
+                format!("{} {}", arbitrage.kof2, arbitrage.kof2_provider),
+                format!("{:.2}", arbitrage.margin),
+                format!("{:.2}", arbitrage.guaranteed_return),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let mut widths = header.clone().map(|cell| cell.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = format_row(&header, &widths);
+    table.push_str(&format_row(&widths.map(|width| "-".repeat(width)), &widths));
+    for (arbitrage, row) in sorted.iter().zip(&rows) {
+        let line = format_row(row, &widths);
+        if color && is_highlighted(arbitrage, min_profit) {
+            table.push_str(&format!("{}{}{}\n", HIGHLIGHT, line.trim_end(), RESET));
+        } else {
+            table.push_str(&line);
+        }
+    }
+
+    table
+}
+
+fn is_highlighted(arbitrage: &PossibleArbitrage, min_profit: f64) -> bool {
+    arbitrage.is_arbitrage && arbitrage.guaranteed_return >= min_profit
+}
+
+fn format_row(cells: &[String; 6], widths: &[usize; 6]) -> String {
+    let mut line = cells
+        .iter()
+        .zip(widths)
+        .enumerate()
+        .map(|(i, (cell, width))| {
+            let padding = " ".repeat(width - cell.chars().count());
+            // Numbers are easier to compare right aligned
+            if i >= 4 {
+                format!("{}{}", padding, cell)
+            } else {
+                format!("{}{}", cell, padding)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ");
+    line.push('\n');
+    line
+}
@@ -6,5 +6,7 @@ use crate::models::SportEvent;
 
 #[async_trait]
 pub trait BookieParser {
+    fn provider(&self) -> &str;
+
     async fn parse(&self) -> Result<Vec<SportEvent>, Box<dyn Error>>;
 }
@@ -1,22 +1,57 @@
 use std::error::Error;
 
 use async_trait::async_trait;
-use playwright::api::Page;
 use scraper::{Html, Selector};
 
-use crate::{models::SportEvent, parser::BookieParser};
+use crate::{content_source::ContentSource, models::SportEvent, parser::BookieParser};
+
+const READY_SELECTOR: &str = "table.aic-hdp-row";
 
 pub struct OlyBetParser {
-    rows_selector: Selector,
-    teams_selector: Selector,
-    odds_selector: Selector,
+    provider: String,
+    pages: Vec<String>,
+    extractor: OlyBetExtractor,
 
-    page: Page,
+    source: Box<dyn ContentSource>,
 }
 
 impl OlyBetParser {
-    pub fn new(page: Page) -> Self {
+    pub fn new(provider: &str, pages: Vec<String>, source: Box<dyn ContentSource>) -> Self {
         OlyBetParser {
+            provider: provider.to_string(),
+            pages,
+            extractor: OlyBetExtractor::new(),
+            source,
+        }
+    }
+}
+
+#[async_trait]
+impl BookieParser for OlyBetParser {
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    async fn parse(&self) -> Result<Vec<SportEvent>, Box<dyn Error>> {
+        let mut events = vec![];
+        for page in &self.pages {
+            let html = self.source.fetch(page, READY_SELECTOR).await?;
+            events.extend(self.extractor.extract(&html, &self.provider, page)?);
+        }
+
+        Ok(events)
+    }
+}
+
+pub struct OlyBetExtractor {
+    rows_selector: Selector,
+    teams_selector: Selector,
+    odds_selector: Selector,
+}
+
+impl OlyBetExtractor {
+    pub fn new() -> Self {
+        OlyBetExtractor {
             rows_selector: Selector::parse("table.aic-hdp-row")
                 .expect("Css selector should have been valid."),
 
@@ -24,15 +59,16 @@ impl OlyBetParser {
                 .expect("Css selector should have been valid."),
 
             odds_selector: Selector::parse("span").expect("Css selector should have been valid."),
-            page,
         }
     }
-}
 
-#[async_trait]
-impl BookieParser for OlyBetParser {
-    async fn parse(&self) -> Result<Vec<SportEvent>, Box<dyn Error>> {
-        let document = Html::parse_document(&self.get_content_from_page().await?);
+    pub fn extract(
+        &self,
+        html: &str,
+        provider: &str,
+        source_url: &str,
+    ) -> Result<Vec<SportEvent>, Box<dyn Error>> {
+        let document = Html::parse_document(html);
 
         let upcoming_events = document.select(&self.rows_selector);
 
@@ -78,34 +114,75 @@ impl BookieParser for OlyBetParser {
             };
 
             let sport_event = SportEvent {
-                team1: team_names.get(0).ok_or("can't find team 1")?.clone(),
+                team1: team_names.first().ok_or("can't find team 1")?.clone(),
                 team2: team_names.get(1).ok_or("can't find team 2")?.clone(),
                 kof1: kof1.clone().parse()?,
                 kof_draw: kof_draw.clone().parse()?,
                 kof2: kof2.clone().parse()?,
-                provider: String::from("olyBet"),
+                provider: provider.to_string(),
+                source_url: source_url.to_string(),
             };
 
             result.push(sport_event)
         }
 
-        return Ok(result);
+        Ok(result)
     }
 }
 
-impl OlyBetParser {
-    async fn get_content_from_page(&self) -> Result<String, Box<dyn Error>> {
-        self.page.goto_builder(
-            "https://sportsbook-lt.orakulas.lt/#/sport/?type=0&sport=1&region=20001&competition=18286520&game=22723272",
-        )
-        // .wait_until(playwright::api::DocumentLoadState::DomContentLoaded)
-        .goto()
-        .await?;
-
-        self.page
-            .wait_for_selector_builder("table.aic-hdp-row")
-            .wait_for_selector()
-            .await?;
-        return Ok(self.page.content().await?);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "https://sportsbook-lt.orakulas.lt/#/sport/?type=0&sport=1&region=20001&competition=18286520";
+
+    fn extract_fixture() -> Vec<SportEvent> {
+        OlyBetExtractor::new()
+            .extract(
+                include_str!("../../tests/fixtures/oly_bet.html"),
+                "olyBet",
+                PAGE,
+            )
+            .expect("fixture should have been parsed")
+    }
+
+    #[test]
+    fn extracts_event_rows() {
+        let events = extract_fixture();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].team1, "Žalgiris Vilnius");
+        assert_eq!(events[0].team2, "Galatasaray");
+        assert_eq!(events[0].kof1, 4.5);
+        assert_eq!(events[0].kof_draw, 3.8);
+        assert_eq!(events[0].kof2, 1.75);
+        assert_eq!(events[0].provider, "olyBet");
+        assert_eq!(events[0].source_url, PAGE);
+        assert_eq!(events[1].team1, "Ferencvarosi TC");
+    }
+
+    #[test]
+    fn skips_locked_odds() {
+        let events = extract_fixture();
+
+        assert!(!events.iter().any(|event| event.team1 == "Olympiacos"));
+    }
+
+    #[test]
+    fn skips_rows_with_empty_odds() {
+        let events = extract_fixture();
+
+        assert!(!events.iter().any(|event| event.team1 == "Sheriff Tiraspol"));
+    }
+
+    #[test]
+    fn fails_on_malformed_odds() {
+        let result = OlyBetExtractor::new().extract(
+            include_str!("../../tests/fixtures/oly_bet_malformed.html"),
+            "olyBet",
+            PAGE,
+        );
+
+        assert!(result.is_err());
     }
 }
@@ -4,19 +4,55 @@ use async_trait::async_trait;
 
 use scraper::{Html, Selector};
 
-use crate::{models::SportEvent, parser::BookieParser};
+use crate::{content_source::ContentSource, models::SportEvent, parser::BookieParser};
 
-use super::http_client_extensions::DefaultChromeHeaders;
+const READY_SELECTOR: &str = r#"*[itemtype="http://schema.org/SportsEvent"]"#;
 
 pub struct TopSportParser {
+    provider: String,
+    pages: Vec<String>,
+    extractor: TopSportExtractor,
+
+    source: Box<dyn ContentSource>,
+}
+
+impl TopSportParser {
+    pub fn new(provider: &str, pages: Vec<String>, source: Box<dyn ContentSource>) -> Self {
+        TopSportParser {
+            provider: provider.to_string(),
+            pages,
+            extractor: TopSportExtractor::new(),
+            source,
+        }
+    }
+}
+
+#[async_trait]
+impl BookieParser for TopSportParser {
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    async fn parse(&self) -> Result<Vec<SportEvent>, Box<dyn Error>> {
+        let mut events = vec![];
+        for page in &self.pages {
+            let html = self.source.fetch(page, READY_SELECTOR).await?;
+            events.extend(self.extractor.extract(&html, &self.provider, page)?);
+        }
+
+        Ok(events)
+    }
+}
+
+pub struct TopSportExtractor {
     div_with_content_selector: Selector,
     meta_tag_with_name_selector: Selector,
     league_rate_span_selector: Selector,
 }
 
-impl TopSportParser {
+impl TopSportExtractor {
     pub fn new() -> Self {
-        TopSportParser {
+        TopSportExtractor {
             div_with_content_selector: Selector::parse(
                 r#"*[itemtype="http://schema.org/SportsEvent"]"#,
             )
@@ -29,22 +65,14 @@ impl TopSportParser {
                 .expect("Css selector should have been valid."),
         }
     }
-}
 
-#[async_trait]
-impl BookieParser for TopSportParser {
-    async fn parse(&self) -> Result<Vec<SportEvent>, Box<dyn Error>> {
-        let client = reqwest::Client::builder().gzip(true).brotli(true).build()?;
-
-        let resp = client
-            .get("https://www.topsport.lt/futbolas/cempionu-lyga")
-            .default_chrome_headers()
-            .send()
-            .await?
-            .text()
-            .await?;
-
-        let document = Html::parse_document(&resp);
+    pub fn extract(
+        &self,
+        html: &str,
+        provider: &str,
+        source_url: &str,
+    ) -> Result<Vec<SportEvent>, Box<dyn Error>> {
+        let document = Html::parse_document(html);
 
         let upcoming_events = document.select(&self.div_with_content_selector);
 
@@ -63,21 +91,85 @@ impl BookieParser for TopSportParser {
 
             let kofs = event_element
                 .select(&self.league_rate_span_selector)
-                .map(|span| span.inner_html())
+                .map(|span| span.inner_html().trim().to_string())
                 .collect::<Vec<_>>();
 
+            if kofs.len() < 3 || kofs.iter().take(3).any(|kof| kof.is_empty()) {
+                // Suspended markets are rendered without rates
+                continue;
+            }
+
             let sport_event = SportEvent {
-                team1: team_names.get(0).ok_or("can't find team 1")?.to_string(),
+                team1: team_names.first().ok_or("can't find team 1")?.to_string(),
                 team2: team_names.get(1).ok_or("can't find team 2")?.to_string(),
-                kof1: kofs.get(0).ok_or("can't find coefficient 1")?.clone().parse()?,
+                kof1: kofs.first().ok_or("can't find coefficient 1")?.clone().parse()?,
                 kof_draw: kofs.get(1).ok_or("can't find draw coefficient")?.clone().parse()?,
                 kof2: kofs.get(2).ok_or("can't find coefficient 2")?.clone().parse()?,
-                provider: String::from("topSport"),
+                provider: provider.to_string(),
+                source_url: source_url.to_string(),
             };
 
             result.push(sport_event)
         }
 
-        return Ok(result);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "https://www.topsport.lt/futbolas/cempionu-lyga";
+
+    fn extract_fixture() -> Vec<SportEvent> {
+        TopSportExtractor::new()
+            .extract(
+                include_str!("../../tests/fixtures/top_sport.html"),
+                "topSport",
+                PAGE,
+            )
+            .expect("fixture should have been parsed")
+    }
+
+    #[test]
+    fn extracts_event_rows() {
+        let events = extract_fixture();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].team1, "FK Žalgiris");
+        assert_eq!(events[0].team2, "Galatasaray");
+        assert_eq!(events[0].kof1, 4.65);
+        assert_eq!(events[0].kof_draw, 3.9);
+        assert_eq!(events[0].kof2, 1.7);
+        assert_eq!(events[0].provider, "topSport");
+        assert_eq!(events[0].source_url, PAGE);
+        assert_eq!(events[1].team1, "Ferencvaros");
+        assert_eq!(events[1].team2, "KÍ Klaksvík");
+    }
+
+    #[test]
+    fn skips_locked_events() {
+        let events = extract_fixture();
+
+        assert!(!events.iter().any(|event| event.team1 == "Olympiakos"));
+    }
+
+    #[test]
+    fn skips_events_with_missing_rates() {
+        let events = extract_fixture();
+
+        assert!(!events.iter().any(|event| event.team1 == "Sheriff"));
+    }
+
+    #[test]
+    fn fails_on_malformed_rates() {
+        let result = TopSportExtractor::new().extract(
+            include_str!("../../tests/fixtures/top_sport_malformed.html"),
+            "topSport",
+            PAGE,
+        );
+
+        assert!(result.is_err());
     }
 }
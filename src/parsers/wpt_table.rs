@@ -0,0 +1,239 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use scraper::{ElementRef, Html, Selector};
+
+use crate::{content_source::ContentSource, models::SportEvent, parser::BookieParser};
+
+const READY_SELECTOR: &str = "div.wpt-odd-changer";
+
+/// Parser for the white-label sportsbook platform (betSafe, cBet, ...) rendering odds in `wpt-table`s.
+pub struct WptTableParser {
+    provider: String,
+    pages: Vec<String>,
+    extractor: WptTableExtractor,
+
+    source: Box<dyn ContentSource>,
+}
+
+impl WptTableParser {
+    pub fn new(provider: &str, pages: Vec<String>, source: Box<dyn ContentSource>) -> Self {
+        WptTableParser {
+            provider: provider.to_string(),
+            pages,
+            extractor: WptTableExtractor::new(),
+            source,
+        }
+    }
+}
+
+#[async_trait]
+impl BookieParser for WptTableParser {
+    fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    async fn parse(&self) -> Result<Vec<SportEvent>, Box<dyn Error>> {
+        let mut events = vec![];
+        for page in &self.pages {
+            let html = self.source.fetch(page, READY_SELECTOR).await?;
+            events.extend(self.extractor.extract(&html, &self.provider, page)?);
+        }
+
+        Ok(events)
+    }
+}
+
+pub struct WptTableExtractor {
+    rows_selector: Selector,
+    teams_selector: Selector,
+    columns_selector: Selector,
+    odds_selector: Selector,
+}
+
+impl WptTableExtractor {
+    pub fn new() -> Self {
+        WptTableExtractor {
+            rows_selector: Selector::parse("div.wpt-table__body > div.wpt-table__row")
+                .expect("Css selector should have been valid."),
+
+            teams_selector: Selector::parse("div.wpt-teams__team > span")
+                .expect("Css selector should have been valid."),
+
+            columns_selector: Selector::parse("div.wpt-table__col")
+                .expect("Css selector should have been valid."),
+
+            odds_selector: Selector::parse("div.wpt-odd-changer")
+                .expect("Css selector should have been valid."),
+        }
+    }
+
+    pub fn extract(
+        &self,
+        html: &str,
+        provider: &str,
+        source_url: &str,
+    ) -> Result<Vec<SportEvent>, Box<dyn Error>> {
+        let document = Html::parse_document(html);
+
+        let upcoming_events = document.select(&self.rows_selector);
+
+        let mut result: Vec<SportEvent> = vec![];
+
+        for event_element in upcoming_events {
+            let team_names = event_element
+                .select(&self.teams_selector)
+                .map(|span| span.inner_html().trim().to_string())
+                .collect::<Vec<_>>();
+
+            let kofs = event_element
+                .select(&self.odds_selector)
+                .map(|span| span.inner_html().trim().to_string())
+                .collect::<Vec<_>>();
+
+            let columns = event_element
+                .select(&self.columns_selector)
+                .collect::<Vec<_>>();
+
+            let is_column_locked = |element: Option<&ElementRef>| -> Result<bool, &str> {
+                Ok(element
+                    .ok_or("")?
+                    .value()
+                    .has_class("locked", scraper::CaseSensitivity::AsciiCaseInsensitive))
+            };
+
+            if is_column_locked(columns.get(1))? || is_column_locked(columns.get(2))? {
+                continue;
+            }
+
+            let kof1 = kofs.first();
+            let kof_draw = kofs.get(1);
+            let kof2 = kofs.get(2);
+
+            if kof1.is_none() || kof_draw.is_none() || kof2.is_none() {
+                // Indicates that there are locks on the first two bets. Might need more robust logic later
+                continue;
+            }
+
+            let sport_event = SportEvent {
+                team1: team_names.first().ok_or("can't find team 1")?.clone(),
+                team2: team_names.get(1).ok_or("can't find team 2")?.clone(),
+                kof1: kofs
+                    .first()
+                    .ok_or("can't find coefficient 1")?
+                    .clone()
+                    .parse()?,
+                kof_draw: kofs
+                    .get(1)
+                    .ok_or("can't find draw coefficient")?
+                    .clone()
+                    .parse()?,
+                kof2: kofs
+                    .get(2)
+                    .ok_or("can't find coefficient 2")?
+                    .clone()
+                    .parse()?,
+                provider: provider.to_string(),
+                source_url: source_url.to_string(),
+            };
+
+            result.push(sport_event)
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = "https://www.betsafe.lt/lt/lazybos/futbolas/europa/uefa-cempionu-lyga";
+
+    fn extract_fixture(html: &str, provider: &str) -> Vec<SportEvent> {
+        WptTableExtractor::new()
+            .extract(html, provider, PAGE)
+            .expect("fixture should have been parsed")
+    }
+
+    #[test]
+    fn extracts_bet_safe_rows() {
+        let events = extract_fixture(
+            include_str!("../../tests/fixtures/bet_safe.html"),
+            "betSafe",
+        );
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].team1, "Žalgiris");
+        assert_eq!(events[0].team2, "Galatasaray");
+        assert_eq!(events[0].kof1, 4.6);
+        assert_eq!(events[0].kof_draw, 3.85);
+        assert_eq!(events[0].kof2, 1.72);
+        assert_eq!(events[0].provider, "betSafe");
+        assert_eq!(events[0].source_url, PAGE);
+        assert_eq!(events[1].team1, "Ferencváros");
+        assert_eq!(events[1].kof2, 10.5);
+    }
+
+    #[test]
+    fn extracts_c_bet_rows() {
+        let events = extract_fixture(include_str!("../../tests/fixtures/c_bet.html"), "cBet");
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].team1, "FK Žalgiris Vilnius");
+        assert_eq!(events[0].team2, "Galatasaray");
+        assert_eq!(events[0].kof1, 4.75);
+        assert_eq!(events[0].kof_draw, 3.95);
+        assert_eq!(events[0].kof2, 1.68);
+        assert_eq!(events[0].provider, "cBet");
+        assert_eq!(events[1].team2, "KI Klaksvik");
+    }
+
+    #[test]
+    fn skips_locked_columns() {
+        let bet_safe_events = extract_fixture(
+            include_str!("../../tests/fixtures/bet_safe.html"),
+            "betSafe",
+        );
+        let c_bet_events = extract_fixture(include_str!("../../tests/fixtures/c_bet.html"), "cBet");
+
+        assert!(!bet_safe_events
+            .iter()
+            .any(|event| event.team1 == "Olympiacos"));
+        assert!(!c_bet_events.iter().any(|event| event.team1 == "Olympiakos"));
+    }
+
+    #[test]
+    fn skips_rows_without_odds() {
+        let bet_safe_events = extract_fixture(
+            include_str!("../../tests/fixtures/bet_safe.html"),
+            "betSafe",
+        );
+        let c_bet_events = extract_fixture(include_str!("../../tests/fixtures/c_bet.html"), "cBet");
+
+        assert!(!bet_safe_events
+            .iter()
+            .any(|event| event.team1 == "Sheriff Tiraspol"));
+        assert!(!c_bet_events.iter().any(|event| event.team1 == "Sheriff"));
+    }
+
+    #[test]
+    fn fails_on_malformed_odds() {
+        let extractor = WptTableExtractor::new();
+
+        assert!(extractor
+            .extract(
+                include_str!("../../tests/fixtures/bet_safe_malformed.html"),
+                "betSafe",
+                PAGE
+            )
+            .is_err());
+        assert!(extractor
+            .extract(
+                include_str!("../../tests/fixtures/c_bet_malformed.html"),
+                "cBet",
+                PAGE
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use std::{error::Error, path::Path};
+
+use playwright::{api::BrowserContext, Playwright};
+
+use crate::{
+    cli::ScrapeArgs,
+    config::{Config, FetchMode, Platform, ProviderConfig},
+    content_source::ContentSource,
+    content_sources::{
+        browser::BrowserSource, file::FileSource, http::HttpSource, recording::RecordingSource,
+    },
+    parser::BookieParser,
+    parsers::{oly_bet::OlyBetParser, top_sport::TopSportParser, wpt_table::WptTableParser},
+};
+
+pub struct Providers {
+    pub parsers: Vec<Box<dyn BookieParser>>,
+    /// Refresh interval of every parser in watch mode, in seconds.
+    pub refresh_intervals: Vec<Option<u64>>,
+    // Playwright shuts the browser down once dropped, it has to outlive the parsers using its pages
+    _browser: Option<(Playwright, BrowserContext)>,
+}
+
+impl Providers {
+    /// Builds parsers for the selected providers, every enabled one when none were selected. With
+    /// snapshots set pages are read from disk instead of the live sites. With a recording directory
+    /// every fetched page is also saved under `<directory>/<provider id>` for `replay`.
+    pub async fn build(config: &Config, args: &ScrapeArgs) -> Result<Providers, Box<dyn Error>> {
+        let mut browser: Option<(Playwright, BrowserContext)> = None;
+        let mut parsers = vec![];
+        let mut refresh_intervals = vec![];
+
+        for provider in config.selected_providers(&args.providers)? {
+            let mut source: Box<dyn ContentSource> = match (&args.snapshots, provider.fetch) {
+                (Some(path), _) => Box::new(FileSource::new(path)),
+                (None, FetchMode::Http) => Box::new(HttpSource::new()?),
+                (None, FetchMode::Browser) => {
+                    if browser.is_none() {
+                        browser = Some(launch_browser().await?);
+                    }
+                    let (_, context) = browser
+                        .as_ref()
+                        .ok_or("browser should have been launched")?;
+                    Box::new(BrowserSource::new(context.new_page().await?))
+                }
+            };
+            if let (Some(directory), Some(first_page)) = (&args.record, provider.pages.first()) {
+                let directory = Path::new(directory).join(&provider.id);
+                source = Box::new(RecordingSource::new(source, directory, first_page));
+            }
+
+            parsers.push(parser_for(provider, source));
+            refresh_intervals.push(provider.refresh_interval);
+        }
+
+        Ok(Providers {
+            parsers,
+            refresh_intervals,
+            _browser: browser,
+        })
+    }
+}
+
+async fn launch_browser() -> Result<(Playwright, BrowserContext), Box<dyn Error>> {
+    let playwright = Playwright::initialize().await?;
+    playwright.prepare()?; // Install browsers
+    let chromium = playwright.chromium();
+    let browser = chromium.launcher().headless(true).launch().await?;
+    let context = browser.context_builder().build().await?;
+
+    Ok((playwright, context))
+}
+
+pub fn parser_for(
+    provider: &ProviderConfig,
+    source: Box<dyn ContentSource>,
+) -> Box<dyn BookieParser> {
+    let pages = provider.pages.clone();
+    match provider.platform {
+        Platform::TopSport => Box::new(TopSportParser::new(&provider.id, pages, source)),
+        Platform::WptTable => Box::new(WptTableParser::new(&provider.id, pages, source)),
+        Platform::OlyBet => Box::new(OlyBetParser::new(&provider.id, pages, source)),
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::{
+    arbitrage::find_arbitrages,
+    config::Config,
+    content_sources::{
+        file::FileSource,
+        recording::{ERROR_FILE_NAME, RUN_DIR_FORMAT},
+    },
+    fees::Fees,
+    lifecycle::{ArbitrageChange, ArbitrageTracker},
+    models::SportEvent,
+    providers::parser_for,
+};
+
+/// Pages of a single parse recorded with `--record`.
+struct RecordedRun {
+    recorded_at: DateTime<Utc>,
+    provider: String,
+    directory: PathBuf,
+}
+
+/// Re-parses every run recorded under `directory` in the order they were recorded and matches them
+/// the same way `watch` does, so a session gives the same arbitrages every time it's replayed.
+/// `emit` receives the arbitrages that appeared, changed or disappeared with every run. A run that
+/// failed while recorded drops its provider's odds again.
+pub async fn replay(
+    config: &Config,
+    directory: &Path,
+    fees: &Fees,
+    mut emit: impl FnMut(DateTime<Utc>, &[ArbitrageChange]),
+) -> Result<(), Box<dyn Error>> {
+    let runs = recorded_runs(directory)?;
+    if runs.is_empty() {
+        return Err(format!("nothing was recorded under {}", directory.display()).into());
+    }
+
+    // Kept in config order, matching depends on the order providers' events are compared in
+    let mut latest_events: Vec<Option<Vec<SportEvent>>> = vec![None; config.providers.len()];
+    let mut tracker = ArbitrageTracker::new(config.notifications.min_profit)
+        .awaiting(runs.iter().map(|run| run.provider.as_str()));
+
+    for run in &runs {
+        let index = config
+            .providers
+            .iter()
+            .position(|provider| provider.id == run.provider)
+            .ok_or_else(|| format!("recorded provider {} isn't configured", run.provider))?;
+        let error_file = run.directory.join(ERROR_FILE_NAME);
+        let parsed = if error_file.exists() {
+            Err(fs::read_to_string(&error_file)?.into())
+        } else {
+            let parser = parser_for(
+                &config.providers[index],
+                Box::new(FileSource::new(&run.directory)),
+            );
+            parser.parse().await
+        };
+
+        latest_events[index] = match parsed {
+            Ok(events) => Some(events),
+            Err(e) => {
+                eprintln!(
+                    "{} failed in {}: {}",
+                    run.provider,
+                    run.directory.display(),
+                    e
+                );
+                None
+            }
+        };
+
+        let events_by_provider = latest_events.iter().flatten().cloned().collect::<Vec<_>>();
+        let arbitrages = if events_by_provider.len() < 2 {
+            vec![]
+        } else {
+            find_arbitrages(events_by_provider, fees)?.0
+        };
+        if !tracker.reported(&run.provider) {
+            continue;
+        }
+        let changes = tracker.update(run.recorded_at, arbitrages);
+        if !changes.is_empty() {
+            emit(run.recorded_at, &changes);
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs laid out as `<directory>/<provider id>/<RUN_DIR_FORMAT>`, oldest first.
+fn recorded_runs(directory: &Path) -> Result<Vec<RecordedRun>, Box<dyn Error>> {
+    let mut runs = vec![];
+    for provider_entry in read_directories(directory)? {
+        let provider = provider_entry.file_name().to_string_lossy().to_string();
+
+        for run_entry in read_directories(&provider_entry.path())? {
+            let name = run_entry.file_name().to_string_lossy().to_string();
+            let recorded_at = NaiveDateTime::parse_from_str(&name, RUN_DIR_FORMAT)
+                .map_err(|e| format!("unexpected recording {}: {}", run_entry.path().display(), e))?
+                .and_utc();
+
+            runs.push(RecordedRun {
+                recorded_at,
+                provider: provider.clone(),
+                directory: run_entry.path(),
+            });
+        }
+    }
+
+    runs.sort_by(|a, b| {
+        a.recorded_at
+            .cmp(&b.recorded_at)
+            .then_with(|| a.provider.cmp(&b.provider))
+    });
+
+    Ok(runs)
+}
+
+fn read_directories(directory: &Path) -> Result<Vec<fs::DirEntry>, Box<dyn Error>> {
+    let entries = fs::read_dir(directory)
+        .map_err(|e| format!("couldn't read recording {}: {}", directory.display(), e))?;
+
+    let mut directories = vec![];
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            directories.push(entry);
+        }
+    }
+
+    Ok(directories)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread, time::Duration};
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{
+        config::ProviderConfig, content_source::ContentSource,
+        content_sources::recording::RecordingSource, lifecycle::ChangeKind,
+    };
+
+    const CONFIG: &str = r#"
+        [[providers]]
+        id = "topSport"
+        platform = "top_sport"
+        pages = ["https://www.topsport.lt/futbolas/cempionu-lyga"]
+
+        [[providers]]
+        id = "betSafe"
+        platform = "wpt_table"
+        pages = ["https://www.betsafe.lt/lt/lazybos/futbolas/europa/uefa-cempionu-lyga-kvalifikacija"]
+    "#;
+
+    /// Serves the same page, or fails, whichever url is asked for.
+    struct FakeSource(Result<String, &'static str>);
+
+    #[async_trait]
+    impl ContentSource for FakeSource {
+        async fn fetch(&self, _url: &str, _ready_selector: &str) -> Result<String, Box<dyn Error>> {
+            self.0.clone().map_err(Into::into)
+        }
+    }
+
+    async fn record(
+        provider: &ProviderConfig,
+        directory: &Path,
+        page: Result<String, &'static str>,
+    ) {
+        let source = RecordingSource::new(
+            Box::new(FakeSource(page)),
+            directory.join(&provider.id),
+            &provider.pages[0],
+        );
+        let _ = parser_for(provider, Box::new(source)).parse().await;
+        // Runs are told apart by the millisecond they were recorded at
+        thread::sleep(Duration::from_millis(2));
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_session_with_failures() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let directory = std::env::temp_dir().join(format!("replay-test-{}", std::process::id()));
+        let (top_sport, bet_safe) = (&config.providers[0], &config.providers[1]);
+        // Galatasaray's win raised from 1.70 so the match becomes an arbitrage
+        let top_sport_page =
+            include_str!("../tests/fixtures/top_sport.html").replace("1.70", "2.50");
+        record(top_sport, &directory, Ok(top_sport_page)).await;
+        record(
+            bet_safe,
+            &directory,
+            Ok(include_str!("../tests/fixtures/bet_safe.html").to_string()),
+        )
+        .await;
+        record(bet_safe, &directory, Err("timed out")).await;
+
+        let mut emitted = vec![];
+        let replayed = replay(&config, &directory, &Fees::default(), |_, changes| {
+            emitted.push(
+                changes
+                    .iter()
+                    .map(|change| change.change)
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .await;
+        fs::remove_dir_all(&directory).unwrap();
+
+        replayed.unwrap();
+        // betSafe's failed run takes its odds, and with them the arbitrage, away again
+        assert_eq!(
+            emitted,
+            [vec![ChangeKind::Appeared], vec![ChangeKind::Disappeared]]
+        );
+    }
+}
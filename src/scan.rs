@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, error::Error, fs, path::Path};
+
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arbitrage::{find_arbitrages, PossibleArbitrage},
+    fees::Fees,
+    models::SportEvent,
+    providers::Providers,
+    storage::Storage,
+};
+
+const MIN_SUCCESSFUL_PROVIDERS: usize = 2;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub timestamp: DateTime<Utc>,
+    pub arbitrages: Vec<PossibleArbitrage>,
+    pub unmatched_events: Vec<SportEvent>,
+    pub failed_providers: Vec<ProviderFailure>,
+    /// Number of events every successful provider returned.
+    pub provider_counts: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderFailure {
+    pub provider: String,
+    pub error: String,
+}
+
+/// Runs every parser once and looks for arbitrages among whichever providers succeeded. Every
+/// scrape, failed ones included, and the matching are recorded to `storage` when given.
+pub async fn scan(
+    providers: &Providers,
+    fees: &Fees,
+    mut storage: Option<&mut Storage>,
+) -> Result<ScanResult, Box<dyn Error>> {
+    let results = join_all(providers.parsers.iter().map(|parser| async move {
+        let result = parser.parse().await;
+        (parser.provider(), Utc::now(), result)
+    }))
+    .await;
+
+    let timestamp = Utc::now();
+
+    let mut events_by_provider = vec![];
+    let mut failed_providers = vec![];
+    let mut provider_counts = BTreeMap::new();
+    for (provider, scraped_at, result) in results {
+        if let Some(storage) = storage.as_deref_mut() {
+            match &result {
+                Ok(events) => storage.save_scrape(provider, scraped_at, Ok(events))?,
+                Err(e) => storage.save_scrape(provider, scraped_at, Err(&e.to_string()))?,
+            }
+        }
+
+        match result {
+            Ok(events) => {
+                provider_counts.insert(provider.to_string(), events.len());
+                events_by_provider.push(events);
+            }
+            Err(e) => failed_providers.push(ProviderFailure {
+                provider: provider.to_string(),
+                error: e.to_string(),
+            }),
+        }
+    }
+
+    if events_by_provider.len() < MIN_SUCCESSFUL_PROVIDERS {
+        let failures = failed_providers
+            .iter()
+            .map(|failure| format!("{}: {}", failure.provider, failure.error))
+            .collect::<Vec<_>>();
+
+        return Err(format!(
+            "only {} of {} providers returned data, at least {} are needed to compare odds ({})",
+            events_by_provider.len(),
+            events_by_provider.len() + failed_providers.len(),
+            MIN_SUCCESSFUL_PROVIDERS,
+            failures.join("; ")
+        )
+        .into());
+    }
+
+    let (arbitrages, unmatched_events) = find_arbitrages(events_by_provider, fees)?;
+
+    // Arbitrages are recorded along with their lifecycles, see `lifecycle::track_once`
+    if let Some(storage) = storage {
+        storage.save_matching(timestamp, arbitrages.len(), unmatched_events.len())?;
+    }
+
+    Ok(ScanResult {
+        timestamp,
+        arbitrages,
+        unmatched_events,
+        failed_providers,
+        provider_counts,
+    })
+}
+
+impl ScanResult {
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| format!("couldn't save results to {}: {}", path.display(), e).into())
+    }
+
+    pub fn load(path: &Path) -> Result<ScanResult, Box<dyn Error>> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read results {}: {}", path.display(), e))?;
+
+        Ok(serde_json::from_str(&content)?)
+    }
+}
@@ -0,0 +1,404 @@
+use std::{collections::HashMap, error::Error};
+
+use serde::Deserialize;
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    fees::{Fees, ProviderFees},
+};
+
+#[derive(Debug, Clone)]
+pub struct StakeLeg {
+    pub provider: String,
+    pub kof: f64,
+    pub stake: f64,
+    pub fees: ProviderFees,
+}
+
+impl StakeLeg {
+    pub fn payout(&self) -> f64 {
+        self.fees.payout(self.stake, self.kof)
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.fees.cost(self.stake)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StakePlan {
+    pub kof1: StakeLeg,
+    pub kof_draw: StakeLeg,
+    pub kof2: StakeLeg,
+    pub total_stake: f64,
+    /// Total stake plus every fee charged for placing the bets.
+    pub total_cost: f64,
+    /// Smallest payout across the three outcomes, i.e. what is returned whichever way the match ends.
+    pub guaranteed_payout: f64,
+    pub guaranteed_profit: f64,
+}
+
+impl StakePlan {
+    pub fn from_legs(kof1: StakeLeg, kof_draw: StakeLeg, kof2: StakeLeg) -> Self {
+        let total_stake = kof1.stake + kof_draw.stake + kof2.stake;
+        let total_cost = kof1.cost() + kof_draw.cost() + kof2.cost();
+        let guaranteed_payout = kof1.payout().min(kof_draw.payout()).min(kof2.payout());
+
+        StakePlan {
+            kof1,
+            kof_draw,
+            kof2,
+            total_stake,
+            total_cost,
+            guaranteed_payout,
+            guaranteed_profit: guaranteed_payout - total_cost,
+        }
+    }
+
+    pub fn legs(&self) -> [&StakeLeg; 3] {
+        [&self.kof1, &self.kof_draw, &self.kof2]
+    }
+
+    fn flat_fees(&self) -> f64 {
+        self.legs().iter().map(|leg| leg.fees.flat_fee).sum()
+    }
+
+    /// Rounds every leg to its provider's increment and re-checks the worst-case profit. Legs that
+    /// would be rounded past their provider's maximum are rounded down instead. Fails when the
+    /// rounded split no longer guarantees a profit.
+    pub fn rounded(
+        &self,
+        rounding: &StakeRounding,
+        limits: &HashMap<String, StakeLimits>,
+    ) -> Result<StakePlan, Box<dyn Error>> {
+        let round_leg = |leg: &StakeLeg| {
+            let max = limits.get(&leg.provider).and_then(|limits| limits.max);
+            let mut stake = rounding.round(&leg.provider, leg.stake);
+            if max.is_some_and(|max| stake > max) {
+                stake = rounding.round_down(&leg.provider, leg.stake);
+            }
+
+            StakeLeg {
+                stake,
+                ..leg.clone()
+            }
+        };
+
+        let plan = StakePlan::from_legs(
+            round_leg(&self.kof1),
+            round_leg(&self.kof_draw),
+            round_leg(&self.kof2),
+        );
+
+        if plan.guaranteed_profit <= 0.0 {
+            return Err(format!(
+                "rounded stakes {:.2}/{:.2}/{:.2} no longer guarantee a profit (worst case {:.2})",
+                plan.kof1.stake, plan.kof_draw.stake, plan.kof2.stake, plan.guaranteed_profit
+            )
+            .into());
+        }
+
+        Ok(plan)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StakeRounding {
+    pub default_increment: f64,
+    pub provider_increments: HashMap<String, f64>,
+}
+
+impl StakeRounding {
+    pub fn new(default_increment: f64) -> Self {
+        StakeRounding {
+            default_increment,
+            provider_increments: HashMap::new(),
+        }
+    }
+
+    fn increment_for(&self, provider: &str) -> f64 {
+        *self
+            .provider_increments
+            .get(provider)
+            .unwrap_or(&self.default_increment)
+    }
+
+    pub fn round(&self, provider: &str, stake: f64) -> f64 {
+        self.snap(provider, stake, f64::round)
+    }
+
+    pub fn round_down(&self, provider: &str, stake: f64) -> f64 {
+        // A stake already on an increment mustn't drop a whole one because of floating point noise
+        self.snap(provider, stake, |increments| (increments + 1e-9).floor())
+    }
+
+    fn snap(&self, provider: &str, stake: f64, to_whole: impl Fn(f64) -> f64) -> f64 {
+        let increment = self.increment_for(provider);
+        if increment <= 0.0 {
+            return stake;
+        }
+
+        // Snap to whole cents afterwards so that e.g. 0.1 increments don't leave 33.300000000000004.
+        (to_whole(stake / increment) * increment * 100.0).round() / 100.0
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StakeLimits {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StakePlanner {
+    pub rounding: StakeRounding,
+    pub limits: HashMap<String, StakeLimits>,
+    pub fees: Fees,
+}
+
+impl StakePlanner {
+    pub fn new(rounding: StakeRounding, fees: Fees) -> Self {
+        StakePlanner {
+            rounding,
+            limits: HashMap::new(),
+            fees,
+        }
+    }
+
+    /// Splits `total_stake` across the legs, scaling the whole position down when a leg would exceed
+    /// its provider's maximum and rejecting it when a leg would fall under its provider's minimum.
+    pub fn plan(
+        &self,
+        arbitrage: &PossibleArbitrage,
+        total_stake: f64,
+    ) -> Result<StakePlan, Box<dyn Error>> {
+        let unlimited_plan = arbitrage.split_stake(total_stake, &self.fees);
+
+        let scale = unlimited_plan
+            .legs()
+            .iter()
+            .filter_map(|leg| {
+                let max = self.limits.get(&leg.provider)?.max?;
+                Some(max / leg.stake)
+            })
+            .fold(1.0, f64::min);
+
+        // Flat fees don't shrink with the position, only the part spent on stakes does.
+        let flat_fees = unlimited_plan.flat_fees();
+        let plan = arbitrage
+            .split_stake(flat_fees + (total_stake - flat_fees) * scale, &self.fees)
+            .rounded(&self.rounding, &self.limits)?;
+
+        for leg in plan.legs() {
+            let limits = match self.limits.get(&leg.provider) {
+                Some(limits) => limits,
+                None => continue,
+            };
+
+            if let Some(min) = limits.min {
+                if leg.stake < min {
+                    return Err(format!(
+                        "stake {:.2} on {} is below its minimum of {:.2}",
+                        leg.stake, leg.provider, min
+                    )
+                    .into());
+                }
+            }
+            if let Some(max) = limits.max {
+                if leg.stake > max {
+                    return Err(format!(
+                        "rounded stake {:.2} on {} exceeds its maximum of {:.2}",
+                        leg.stake, leg.provider, max
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+impl PossibleArbitrage {
+    /// Splits `total_stake` (fees included) across the three legs so that every outcome pays out the
+    /// same amount after fees and taxes.
+    pub fn split_stake(&self, total_stake: f64, fees: &Fees) -> StakePlan {
+        let leg_fees = [
+            fees.for_provider(&self.kof1_provider),
+            fees.for_provider(&self.kof_draw_provider),
+            fees.for_provider(&self.kof2_provider),
+        ];
+        let flat_fees: f64 = leg_fees.iter().map(|fees| fees.flat_fee).sum();
+        let spendable = total_stake - flat_fees;
+
+        let leg = |provider: &String, kof: f64, effective_kof: f64, fees: &ProviderFees| {
+            let spent = spendable / effective_kof / self.overround;
+            StakeLeg {
+                provider: provider.clone(),
+                kof,
+                stake: spent / (1.0 + fees.stake_fee / 100.0),
+                fees: fees.clone(),
+            }
+        };
+
+        StakePlan::from_legs(
+            leg(
+                &self.kof1_provider,
+                self.kof1,
+                self.effective_kof1,
+                &leg_fees[0],
+            ),
+            leg(
+                &self.kof_draw_provider,
+                self.kof_draw,
+                self.effective_kof_draw,
+                &leg_fees[1],
+            ),
+            leg(
+                &self.kof2_provider,
+                self.kof2,
+                self.effective_kof2,
+                &leg_fees[2],
+            ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{self, assert_close, event};
+
+    /// Best prices 2.2 at topSport, 4.0 and 4.5 at betSafe, a 7.9% guaranteed return without fees.
+    fn arbitrage(fees: &Fees) -> PossibleArbitrage {
+        test_support::arbitrage(
+            vec![
+                event("topSport", 2.2, 3.0, 3.0),
+                event("betSafe", 1.8, 4.0, 4.5),
+            ],
+            fees,
+        )
+    }
+
+    fn planner(increment: f64, limits: &[(&str, StakeLimits)]) -> StakePlanner {
+        let mut planner = StakePlanner::new(StakeRounding::new(increment), Fees::default());
+        for (provider, provider_limits) in limits {
+            planner
+                .limits
+                .insert(String::from(*provider), provider_limits.clone());
+        }
+        planner
+    }
+
+    #[test]
+    fn splits_stake_into_equal_payouts() {
+        let plan = arbitrage(&Fees::default()).split_stake(100.0, &Fees::default());
+
+        assert_close(plan.total_stake, 100.0);
+        for leg in plan.legs() {
+            assert_close(leg.payout(), plan.guaranteed_payout);
+        }
+        assert_close(plan.guaranteed_profit, 100.0 / 0.9267676767676768 - 100.0);
+    }
+
+    #[test]
+    fn splits_stake_into_equal_payouts_after_fees() {
+        let mut fees = Fees::default();
+        fees.providers.insert(
+            String::from("topSport"),
+            ProviderFees {
+                stake_fee: 2.0,
+                flat_fee: 0.1,
+                ..ProviderFees::default()
+            },
+        );
+        fees.providers.insert(
+            String::from("betSafe"),
+            ProviderFees {
+                winnings_tax: 15.0,
+                ..ProviderFees::default()
+            },
+        );
+
+        let plan = arbitrage(&fees).split_stake(100.0, &fees);
+
+        assert_close(plan.total_cost, 100.0);
+        for leg in plan.legs() {
+            assert_close(leg.payout(), plan.guaranteed_payout);
+        }
+        assert!(plan.guaranteed_profit > 0.0);
+    }
+
+    #[test]
+    fn rounds_stakes_to_increments() {
+        let plan = planner(1.0, &[])
+            .plan(&arbitrage(&Fees::default()), 100.0)
+            .expect("rounded stakes should still be profitable");
+
+        let stakes = plan.legs().map(|leg| leg.stake);
+        assert_eq!(stakes, [49.0, 27.0, 24.0]);
+        assert_close(plan.guaranteed_payout, 107.8);
+    }
+
+    #[test]
+    fn rejects_rounding_that_loses_the_profit() {
+        let error = planner(50.0, &[])
+            .plan(&arbitrage(&Fees::default()), 100.0)
+            .expect_err("stakes of 50/50/0 aren't an arbitrage");
+
+        assert!(error.to_string().contains("no longer guarantee a profit"));
+    }
+
+    #[test]
+    fn rounds_down_to_increments() {
+        let rounding = StakeRounding::new(0.1);
+
+        assert_eq!(rounding.round_down("topSport", 33.39), 33.3);
+        assert_eq!(rounding.round_down("topSport", 33.3), 33.3);
+        assert_eq!(rounding.round("topSport", 33.36), 33.4);
+    }
+
+    #[test]
+    fn scales_position_down_to_maximum() {
+        let limits = StakeLimits {
+            min: None,
+            max: Some(40.0),
+        };
+        let plan = planner(0.0, &[("topSport", limits)])
+            .plan(&arbitrage(&Fees::default()), 100.0)
+            .expect("scaled down stakes should still be profitable");
+
+        assert_close(plan.kof1.stake, 40.0);
+        for leg in plan.legs() {
+            assert_close(leg.payout(), 88.0);
+        }
+    }
+
+    #[test]
+    fn rounds_capped_leg_down_to_its_maximum() {
+        let limits = StakeLimits {
+            min: None,
+            max: Some(48.0),
+        };
+        let plan = planner(5.0, &[("topSport", limits)])
+            .plan(&arbitrage(&Fees::default()), 100.0)
+            .expect("rounding down keeps the capped leg within its maximum");
+
+        let stakes = plan.legs().map(|leg| leg.stake);
+        assert_eq!(stakes, [45.0, 25.0, 25.0]);
+        assert!(plan.guaranteed_profit > 0.0);
+    }
+
+    #[test]
+    fn rejects_leg_below_minimum() {
+        let limits = StakeLimits {
+            min: Some(30.0),
+            max: None,
+        };
+        let error = planner(0.0, &[("betSafe", limits)])
+            .plan(&arbitrage(&Fees::default()), 100.0)
+            .expect_err("the draw stake is under 30");
+
+        assert!(error.to_string().contains("below its minimum"));
+    }
+}
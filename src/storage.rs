@@ -0,0 +1,439 @@
+use std::error::Error;
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
+
+use crate::{
+    arbitrage::PossibleArbitrage,
+    lifecycle::{ArbitrageChange, ArbitrageTracker, ChangeKind, Lifecycle},
+    models::SportEvent,
+};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scrapes (
+    id INTEGER PRIMARY KEY,
+    provider TEXT NOT NULL,
+    scraped_at TEXT NOT NULL,
+    error TEXT
+);
+
+CREATE TABLE IF NOT EXISTS odds (
+    id INTEGER PRIMARY KEY,
+    scrape_id INTEGER NOT NULL REFERENCES scrapes(id),
+    team1 TEXT NOT NULL,
+    team2 TEXT NOT NULL,
+    kof1 REAL NOT NULL,
+    kof_draw REAL NOT NULL,
+    kof2 REAL NOT NULL,
+    source_url TEXT NOT NULL
+);
+
+-- One row every time an arbitrage appeared or its prices changed
+CREATE TABLE IF NOT EXISTS arbitrages (
+    id INTEGER PRIMARY KEY,
+    detected_at TEXT NOT NULL,
+    arbitrage_id TEXT NOT NULL,
+    team1 TEXT NOT NULL,
+    team2 TEXT NOT NULL,
+    kof1 REAL NOT NULL,
+    kof1_provider TEXT NOT NULL,
+    kof_draw REAL NOT NULL,
+    kof_draw_provider TEXT NOT NULL,
+    kof2 REAL NOT NULL,
+    kof2_provider TEXT NOT NULL,
+    overround REAL NOT NULL,
+    guaranteed_return REAL NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS matchings (
+    id INTEGER PRIMARY KEY,
+    matched_at TEXT NOT NULL,
+    matched_events INTEGER NOT NULL,
+    unmatched_events INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS lifecycles (
+    id INTEGER PRIMARY KEY,
+    arbitrage_id TEXT NOT NULL,
+    team1 TEXT NOT NULL,
+    team2 TEXT NOT NULL,
+    first_seen TEXT NOT NULL,
+    last_seen TEXT NOT NULL,
+    peak_return REAL NOT NULL,
+    announced INTEGER NOT NULL,
+    ended_at TEXT,
+    -- Latest PossibleArbitrage as JSON, to tell whether its prices changed once tracking resumes
+    arbitrage TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS scrapes_provider_scraped_at ON scrapes (provider, scraped_at);
+CREATE INDEX IF NOT EXISTS odds_scrape_id ON odds (scrape_id);
+CREATE INDEX IF NOT EXISTS arbitrages_detected_at ON arbitrages (detected_at);
+CREATE INDEX IF NOT EXISTS matchings_matched_at ON matchings (matched_at);
+CREATE UNIQUE INDEX IF NOT EXISTS lifecycles_open ON lifecycles (arbitrage_id) WHERE ended_at IS NULL;
+";
+
+/// Scrape of a single provider read back from the database.
+pub struct Scrape {
+    pub provider: String,
+    pub scraped_at: DateTime<Utc>,
+    pub events: Vec<SportEvent>,
+    /// Why the provider failed, there are no events then.
+    pub error: Option<String>,
+}
+
+/// Lifecycles of an arbitrage around within a period, summed up.
+pub struct ArbitrageSummary {
+    pub team1: String,
+    pub team2: String,
+    pub first_seen: DateTime<Utc>,
+    pub best_return: f64,
+    /// Times it appeared.
+    pub lifecycles: u32,
+    /// Seconds the longest of its lifecycles lasted.
+    pub longest_duration: i64,
+}
+
+/// Failed scrapes of a provider within a period.
+pub struct FailureSummary {
+    pub provider: String,
+    pub failures: u32,
+    pub scrapes: u32,
+    pub last_error: String,
+}
+
+/// Matchings of providers' odds within a period and the events left without a counterpart.
+pub struct MatchingSummary {
+    pub matchings: u32,
+    pub average_unmatched_events: f64,
+    pub max_unmatched_events: u32,
+}
+
+/// SQLite database keeping every scrape, the odds it returned and the arbitrages detected from them.
+pub struct Storage {
+    connection: Connection,
+}
+
+impl Storage {
+    pub fn open(path: &str) -> Result<Storage, Box<dyn Error>> {
+        let connection = Connection::open(path)
+            .map_err(|e| format!("couldn't open database {}: {}", path, e))?;
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(Storage { connection })
+    }
+
+    /// Records a parse of `provider`, either the events it returned or why it failed.
+    pub fn save_scrape(
+        &mut self,
+        provider: &str,
+        scraped_at: DateTime<Utc>,
+        result: Result<&[SportEvent], &str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let transaction = self.connection.transaction()?;
+
+        transaction.execute(
+            "INSERT INTO scrapes (provider, scraped_at, error) VALUES (?1, ?2, ?3)",
+            params![provider, scraped_at, result.err()],
+        )?;
+        let scrape_id = transaction.last_insert_rowid();
+
+        if let Ok(events) = result {
+            let mut statement = transaction.prepare(
+                "INSERT INTO odds (scrape_id, team1, team2, kof1, kof_draw, kof2, source_url)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for event in events {
+                statement.execute(params![
+                    scrape_id,
+                    event.team1,
+                    event.team2,
+                    event.kof1,
+                    event.kof_draw,
+                    event.kof2,
+                    event.source_url
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Records how many events were matched across providers and how many found no counterpart.
+    pub fn save_matching(
+        &self,
+        matched_at: DateTime<Utc>,
+        matched_events: usize,
+        unmatched_events: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        self.connection.execute(
+            "INSERT INTO matchings (matched_at, matched_events, unmatched_events) VALUES (?1, ?2, ?3)",
+            params![matched_at, matched_events as i64, unmatched_events as i64],
+        )?;
+
+        Ok(())
+    }
+
+    /// Records the arbitrages among `changes` that appeared or changed, disappearing ends their
+    /// lifecycle instead.
+    pub fn save_arbitrages(
+        &mut self,
+        detected_at: DateTime<Utc>,
+        changes: &[ArbitrageChange],
+    ) -> Result<(), Box<dyn Error>> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO arbitrages (
+                    detected_at, arbitrage_id, team1, team2, kof1, kof1_provider, kof_draw,
+                    kof_draw_provider, kof2, kof2_provider, overround, guaranteed_return
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            )?;
+            let arbitrages = changes
+                .iter()
+                .filter(|change| change.change != ChangeKind::Disappeared)
+                .map(|change| &change.arbitrage);
+            for arbitrage in arbitrages {
+                statement.execute(params![
+                    detected_at,
+                    arbitrage.id,
+                    arbitrage.team1,
+                    arbitrage.team2,
+                    arbitrage.kof1,
+                    arbitrage.kof1_provider,
+                    arbitrage.kof_draw,
+                    arbitrage.kof_draw_provider,
+                    arbitrage.kof2,
+                    arbitrage.kof2_provider,
+                    arbitrage.overround,
+                    arbitrage.guaranteed_return
+                ])?;
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// Scrapes ordered by time, failed ones included, optionally limited to a provider and to those
+    /// taken from `since` and before `until`.
+    pub fn scrapes(
+        &self,
+        provider: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Scrape>, Box<dyn Error>> {
+        let mut statement = self.connection.prepare(
+            "SELECT scrapes.id, scrapes.provider, scrapes.scraped_at, scrapes.error,
+                    odds.team1, odds.team2, odds.kof1, odds.kof_draw, odds.kof2, odds.source_url
+             FROM scrapes
+             LEFT JOIN odds ON odds.scrape_id = scrapes.id
+             WHERE (?1 IS NULL OR scrapes.provider = ?1)
+               AND (?2 IS NULL OR scrapes.scraped_at >= ?2)
+               AND (?3 IS NULL OR scrapes.scraped_at < ?3)
+             ORDER BY scrapes.scraped_at, scrapes.id, odds.id",
+        )?;
+        let mut rows = statement.query(params![provider, since, until])?;
+
+        let mut scrapes: Vec<Scrape> = vec![];
+        let mut last_scrape_id = None;
+        while let Some(row) = rows.next()? {
+            let scrape_id: i64 = row.get(0)?;
+            if last_scrape_id != Some(scrape_id) {
+                last_scrape_id = Some(scrape_id);
+                scrapes.push(Scrape {
+                    provider: row.get(1)?,
+                    scraped_at: row.get(2)?,
+                    events: vec![],
+                    error: row.get(3)?,
+                });
+            }
+
+            // Failed scrapes and those that found no events still take part, they have no odds rows
+            // to join
+            let Some(team1) = row.get::<_, Option<String>>(4)? else {
+                continue;
+            };
+            let scrape = scrapes.last_mut().ok_or("")?;
+            scrape.events.push(SportEvent {
+                team1,
+                team2: row.get(5)?,
+                kof1: row.get(6)?,
+                kof_draw: row.get(7)?,
+                kof2: row.get(8)?,
+                provider: scrape.provider.clone(),
+                source_url: row.get(9)?,
+            });
+        }
+
+        Ok(scrapes)
+    }
+
+    /// Arbitrages whose lifecycles overlap `since` until `until`, the most profitable first.
+    pub fn arbitrage_summaries(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ArbitrageSummary>, Box<dyn Error>> {
+        // Teams are named the way the most profitable of an arbitrage's lifecycles was
+        let mut statement = self.connection.prepare(
+            "SELECT team1, team2, first_seen, best_return, lifecycles, longest_duration
+             FROM (
+                 SELECT team1, team2,
+                        MIN(first_seen) OVER arbitrage AS first_seen,
+                        MAX(peak_return) OVER arbitrage AS best_return,
+                        COUNT(*) OVER arbitrage AS lifecycles,
+                        MAX(CAST(ROUND((julianday(last_seen) - julianday(first_seen)) * 86400) AS INTEGER))
+                            OVER arbitrage AS longest_duration,
+                        ROW_NUMBER() OVER (PARTITION BY arbitrage_id ORDER BY peak_return DESC) AS rank
+                 FROM lifecycles
+                 WHERE first_seen < ?2 AND last_seen >= ?1
+                 WINDOW arbitrage AS (PARTITION BY arbitrage_id)
+             )
+             WHERE rank = 1
+             ORDER BY best_return DESC",
+        )?;
+        let summaries = statement
+            .query_map(params![since, until], |row| {
+                Ok(ArbitrageSummary {
+                    team1: row.get(0)?,
+                    team2: row.get(1)?,
+                    first_seen: row.get(2)?,
+                    best_return: row.get(3)?,
+                    lifecycles: row.get(4)?,
+                    longest_duration: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(summaries)
+    }
+
+    /// Providers that failed at least once from `since` and before `until`.
+    pub fn failure_summaries(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<FailureSummary>, Box<dyn Error>> {
+        // With a single MAX, SQLite takes the bare `error` column from the row holding it, which is
+        // the latest failure as successful scrapes count as NULL
+        let mut statement = self.connection.prepare(
+            "SELECT provider, COUNT(error), COUNT(*), error,
+                    MAX(CASE WHEN error IS NULL THEN NULL ELSE scraped_at END)
+             FROM scrapes
+             WHERE scraped_at >= ?1 AND scraped_at < ?2
+             GROUP BY provider
+             HAVING COUNT(error) > 0
+             ORDER BY provider",
+        )?;
+        let summaries = statement
+            .query_map(params![since, until], |row| {
+                Ok(FailureSummary {
+                    provider: row.get(0)?,
+                    failures: row.get(1)?,
+                    scrapes: row.get(2)?,
+                    last_error: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(summaries)
+    }
+
+    pub fn matching_summary(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<MatchingSummary, Box<dyn Error>> {
+        let summary = self.connection.query_row(
+            "SELECT COUNT(*), COALESCE(AVG(unmatched_events), 0), COALESCE(MAX(unmatched_events), 0)
+             FROM matchings
+             WHERE matched_at >= ?1 AND matched_at < ?2",
+            params![since, until],
+            |row| {
+                Ok(MatchingSummary {
+                    matchings: row.get(0)?,
+                    average_unmatched_events: row.get(1)?,
+                    max_unmatched_events: row.get(2)?,
+                })
+            },
+        )?;
+
+        Ok(summary)
+    }
+
+    /// Lifecycles of arbitrages that hadn't disappeared yet when the last run stopped tracking them.
+    pub fn open_lifecycles(&self) -> Result<Vec<(Lifecycle, PossibleArbitrage)>, Box<dyn Error>> {
+        let mut statement = self.connection.prepare(
+            "SELECT arbitrage_id, first_seen, last_seen, peak_return, announced, arbitrage
+             FROM lifecycles
+             WHERE ended_at IS NULL",
+        )?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    Lifecycle {
+                        id: row.get(0)?,
+                        first_seen: row.get(1)?,
+                        last_seen: row.get(2)?,
+                        peak_return: row.get(3)?,
+                        announced: row.get(4)?,
+                    },
+                    row.get::<_, String>(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        rows.into_iter()
+            .map(|(lifecycle, arbitrage)| Ok((lifecycle, serde_json::from_str(&arbitrage)?)))
+            .collect()
+    }
+
+    /// Records the lifecycles `tracker` currently holds and ends those of disappeared arbitrages.
+    pub fn save_lifecycles(
+        &mut self,
+        timestamp: DateTime<Utc>,
+        tracker: &ArbitrageTracker,
+        changes: &[ArbitrageChange],
+    ) -> Result<(), Box<dyn Error>> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut upsert = transaction.prepare(
+                "INSERT INTO lifecycles (
+                    arbitrage_id, team1, team2, first_seen, last_seen, peak_return, announced,
+                    arbitrage
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT (arbitrage_id) WHERE ended_at IS NULL DO UPDATE SET
+                    last_seen = excluded.last_seen,
+                    peak_return = excluded.peak_return,
+                    announced = excluded.announced,
+                    arbitrage = excluded.arbitrage",
+            )?;
+            for (lifecycle, arbitrage) in tracker.current() {
+                upsert.execute(params![
+                    lifecycle.id,
+                    arbitrage.team1,
+                    arbitrage.team2,
+                    lifecycle.first_seen,
+                    lifecycle.last_seen,
+                    lifecycle.peak_return,
+                    lifecycle.announced,
+                    serde_json::to_string(arbitrage)?
+                ])?;
+            }
+
+            let mut end = transaction.prepare(
+                "UPDATE lifecycles SET ended_at = ?1 WHERE arbitrage_id = ?2 AND ended_at IS NULL",
+            )?;
+            for change in changes {
+                if change.change == ChangeKind::Disappeared {
+                    end.execute(params![timestamp, change.lifecycle.id])?;
+                }
+            }
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use chrono::{DateTime, TimeZone, Utc};
+
+use crate::{
+    arbitrage::{find_arbitrages, PossibleArbitrage},
+    fees::Fees,
+    models::SportEvent,
+    storage::Scrape,
+};
+
+/// Žalgiris - Galatasaray as offered by `provider`.
+pub fn event(provider: &str, kof1: f64, kof_draw: f64, kof2: f64) -> SportEvent {
+    named_event(provider, "Žalgiris", "Galatasaray", [kof1, kof_draw, kof2])
+}
+
+/// Event under the provider's own team names, with 1/X/2 `kofs`.
+pub fn named_event(provider: &str, team1: &str, team2: &str, kofs: [f64; 3]) -> SportEvent {
+    SportEvent {
+        team1: String::from(team1),
+        team2: String::from(team2),
+        kof1: kofs[0],
+        kof2: kofs[2],
+        kof_draw: kofs[1],
+        provider: String::from(provider),
+        source_url: String::new(),
+    }
+}
+
+/// Matches `events`, one per provider, into a single arbitrage.
+pub fn arbitrage(events: Vec<SportEvent>, fees: &Fees) -> PossibleArbitrage {
+    let events_by_provider = events.into_iter().map(|event| vec![event]).collect();
+
+    find_arbitrages(events_by_provider, fees)
+        .expect("events should have been matched")
+        .0
+        .remove(0)
+}
+
+/// Scrape of Žalgiris - Galatasaray by `provider` at `second`, a failed one without `kofs`.
+pub fn scrape(provider: &str, second: u32, kofs: Option<[f64; 3]>) -> Scrape {
+    let events = kofs.map(|[kof1, kof_draw, kof2]| event(provider, kof1, kof_draw, kof2));
+
+    Scrape {
+        provider: String::from(provider),
+        scraped_at: at(second),
+        events: events.into_iter().collect(),
+        error: kofs.is_none().then(|| String::from("timed out")),
+    }
+}
+
+pub fn at(second: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 8, 1, 18, 0, second).unwrap()
+}
+
+pub fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
@@ -0,0 +1,159 @@
+use std::{error::Error, time::Duration};
+
+use chrono::{DateTime, Utc};
+use futures::{stream::FuturesUnordered, StreamExt};
+
+use crate::{
+    arbitrage::find_arbitrages,
+    config::Config,
+    fees::Fees,
+    lifecycle::{ArbitrageChange, ArbitrageTracker},
+    live_state::LiveState,
+    models::SportEvent,
+    movements::diff_events,
+    parser::BookieParser,
+    providers::Providers,
+    storage::Storage,
+};
+
+/// Re-parses every provider on its own interval and recomputes arbitrages whenever one of them
+/// returns. `emit` receives the arbitrages that appeared, changed or disappeared since the previous
+/// update, once every provider has returned for the first time. Every scrape, the arbitrages that
+/// appeared or changed and their lifecycles are recorded to `storage`, whose unfinished lifecycles
+/// are picked up again on start. Prices of a provider moving by at least the configured
+/// `sharp_move` percent between its parses are reported on stderr. `state` always holds the latest
+/// odds, matching and provider health.
+pub async fn watch(
+    providers: &Providers,
+    intervals: &[Duration],
+    fees: &Fees,
+    config: &Config,
+    mut storage: Option<&mut Storage>,
+    state: &LiveState,
+    mut emit: impl FnMut(DateTime<Utc>, &[ArbitrageChange]),
+) {
+    let mut pending = FuturesUnordered::new();
+    for (index, parser) in providers.parsers.iter().enumerate() {
+        pending.push(poll(index, parser.as_ref(), Duration::ZERO));
+    }
+
+    let mut latest_events: Vec<Option<Vec<SportEvent>>> = vec![None; providers.parsers.len()];
+    let mut previous_parses: Vec<Option<(DateTime<Utc>, Vec<SportEvent>)>> =
+        vec![None; providers.parsers.len()];
+    let tracker = ArbitrageTracker::new(config.notifications.min_profit)
+        .awaiting(providers.parsers.iter().map(|parser| parser.provider()));
+    let mut tracker = match storage.as_deref().map(Storage::open_lifecycles) {
+        Some(Ok(lifecycles)) => tracker.resume(lifecycles),
+        Some(Err(e)) => {
+            eprintln!("couldn't resume arbitrage lifecycles: {}", e);
+            tracker
+        }
+        None => tracker,
+    };
+
+    while let Some((index, result)) = pending.next().await {
+        let parser = providers.parsers[index].as_ref();
+        pending.push(poll(index, parser, intervals[index]));
+        let scraped_at = Utc::now();
+        let all_reported = tracker.reported(parser.provider());
+
+        if let Some(storage) = storage.as_deref_mut() {
+            let saved = match &result {
+                Ok(events) => storage.save_scrape(parser.provider(), scraped_at, Ok(events)),
+                Err(e) => storage.save_scrape(parser.provider(), scraped_at, Err(&e.to_string())),
+            };
+            if let Err(e) = saved {
+                eprintln!("couldn't save {} odds: {}", parser.provider(), e);
+            }
+        }
+
+        match &result {
+            Ok(events) => state.record_parse(parser.provider(), scraped_at, Ok(events)),
+            Err(e) => state.record_parse(parser.provider(), scraped_at, Err(&e.to_string())),
+        }
+
+        if let Ok(events) = &result {
+            // Compared with the last successful parse, a failure in between doesn't reset the prices
+            if let Some((previous_scraped_at, previous_events)) = &previous_parses[index] {
+                let movements = diff_events(
+                    previous_events,
+                    *previous_scraped_at,
+                    events,
+                    scraped_at,
+                    config.movements.sharp_move,
+                );
+                for movement in movements.iter().filter(|movement| movement.is_sharp) {
+                    eprintln!("{}", movement);
+                }
+            }
+            previous_parses[index] = Some((scraped_at, events.clone()));
+        }
+
+        latest_events[index] = match result {
+            Ok(events) => Some(events),
+            Err(e) => {
+                // Stale odds of a failing provider would keep reporting arbitrages that are long gone
+                eprintln!("{} failed: {}", parser.provider(), e);
+                None
+            }
+        };
+
+        let events_by_provider = latest_events.iter().flatten().cloned().collect::<Vec<_>>();
+        // With a single provider left nothing can be compared, whatever was found is gone
+        let arbitrages = if events_by_provider.len() < 2 {
+            vec![]
+        } else {
+            match find_arbitrages(events_by_provider, fees) {
+                Ok((arbitrages, unmatched_events)) => {
+                    if let Some(storage) = storage.as_deref_mut() {
+                        let saved = storage.save_matching(
+                            scraped_at,
+                            arbitrages.len(),
+                            unmatched_events.len(),
+                        );
+                        if let Err(e) = saved {
+                            eprintln!("couldn't save matching: {}", e);
+                        }
+                    }
+                    arbitrages
+                }
+                Err(e) => {
+                    eprintln!("couldn't match events: {}", e);
+                    continue;
+                }
+            }
+        };
+        state.record_matching(scraped_at, &arbitrages);
+
+        // Arbitrages resumed from storage would disappear for lack of providers that haven't
+        // returned yet, only to appear again once they have
+        if !all_reported {
+            continue;
+        }
+
+        let changes = tracker.update(scraped_at, arbitrages);
+        state.publish(&changes);
+
+        if let Some(storage) = storage.as_deref_mut() {
+            if let Err(e) = storage.save_arbitrages(scraped_at, &changes) {
+                eprintln!("couldn't save arbitrages: {}", e);
+            }
+            if let Err(e) = storage.save_lifecycles(scraped_at, &tracker, &changes) {
+                eprintln!("couldn't save arbitrage lifecycles: {}", e);
+            }
+        }
+
+        if !changes.is_empty() {
+            emit(scraped_at, &changes);
+        }
+    }
+}
+
+async fn poll(
+    index: usize,
+    parser: &dyn BookieParser,
+    delay: Duration,
+) -> (usize, Result<Vec<SportEvent>, Box<dyn Error>>) {
+    tokio::time::sleep(delay).await;
+    (index, parser.parse().await)
+}